@@ -1,10 +1,21 @@
-use std::{time::Duration, thread};
+use std::{collections::VecDeque, ffi::OsString, io::{BufRead, IsTerminal}, time::Duration, thread, sync::{Arc, LazyLock, atomic::{AtomicBool, Ordering}}};
 
-use crossterm::terminal::{enable_raw_mode, disable_raw_mode};
+use crossterm::{terminal::{enable_raw_mode, disable_raw_mode}, event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers}};
 use strum::IntoEnumIterator;
-use colored::Colorize;
+use colored::{ColoredString, Colorize};
 
-use crate::{farm::{Farm, Crop, Field}, util};
+use crate::{config::GameConfig, farm::{Farm, Crop, Difficulty, FieldSort, FieldView, GameEvent, Skill, Tier}, lang::{self, Message}, leaderboard::Leaderboard, money::Money, slots::{self, SaveSlot}, util::{self, LoadError}};
+
+/// Set when the player presses Ctrl-C, input functions return early so the main loop can offer to save
+static INTERRUPTED: LazyLock<Arc<AtomicBool>> = LazyLock::new(Default::default);
+/// Set once stdin is closed, after which every prompt returns straight away
+static STDIN_CLOSED: AtomicBool = AtomicBool::new(false);
+
+/// Without `--save` or `CLI_FARM_SAVE` the game is saved in slot 1 of this directory
+const DEFAULT_SAVE_DIR: &str = ".";
+const MAX_NOTIFICATIONS: usize = 5;
+const CONFIG_PATH: &str = "config.json";
+const DEFAULT_CSV_PATH: &str = "farm.csv";
 
 fn print_header(name: Option<&str>) {
     let name = match name {
@@ -14,61 +25,255 @@ fn print_header(name: Option<&str>) {
     println!("{}", format!("Welcome to {} farm!", name).bold().bright_green().underline());
 }
 
-fn format_money(money: f64) -> String {
-    format!("{}",format!("${:.2}", money).bold().bright_green())
+fn format_money(money: Money) -> String {
+    format!("{}", util::format_money(money).bold().bright_green())
+}
+
+/// When the field can be harvested: a green NOW when ready, a magenta countdown while growing and gray when not planted
+fn readiness_style(view: &FieldView) -> ColoredString {
+    match view.remaining_ms {
+        Some(0) => "NOW".bold().bright_green(),
+        Some(remaining) => format!("in {}", util::format_duration_ms(remaining)).bold().bright_magenta(),
+        None => "not planted".bright_black(),
+    }
+}
+
+/// The level up price, or MAX if the field can't be leveled up further
+fn format_level_up_price(view: &FieldView) -> String {
+    match view.level_up_price {
+        Some(price) => format_money(price),
+        None => "MAX".bold().red().to_string(),
+    }
+}
+
+/// Command line flags, see `HELP`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Options {
+    pub save_path: Option<String>,
+    pub no_color: bool,
+    pub difficulty: Option<Difficulty>,
+    pub status: bool,
+    pub leaderboard: Option<String>,
+    pub version: bool,
+    pub help: bool,
+}
+
+pub const HELP: &str = "\
+Usage: cli_farm [OPTIONS]
+
+Options:
+  --save <path>          Where the game is saved, defaults to $CLI_FARM_SAVE or slot1.json
+  --no-color             Disable colors, also done by a non-empty NO_COLOR env var
+  --difficulty <level>   Difficulty of a new game: easy, normal or hard
+  --status               Print a one line summary of the saved farm and exit
+  --leaderboard <dir>    Rank the saves in a directory by net worth and exit
+  --version              Print version information and exit
+  --help                 Print this help and exit";
+
+impl Options {
+    /// Parses the arguments after the program name, unknown arguments are ignored
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Options {
+        let mut options = Options::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--save" => options.save_path = args.next().or(options.save_path),
+                "--no-color" => options.no_color = true,
+                "--difficulty" => options.difficulty = args.next().and_then(|level| parse_difficulty(&level)),
+                "--status" => options.status = true,
+                "--leaderboard" => options.leaderboard = Some(args.next().unwrap_or_else(|| ".".to_string())),
+                "--version" | "-V" => options.version = true,
+                "--help" | "-h" => options.help = true,
+                _ => {},
+            }
+        }
+        options
+    }
+
+    pub fn from_env() -> Options {
+        Options::parse(std::env::args().skip(1))
+    }
+
+    /// Where the game is saved: `--save <path>`, then the `CLI_FARM_SAVE` env var, then save slot 1
+    pub fn save_path(&self) -> String {
+        self.resolve_save_path(std::env::var("CLI_FARM_SAVE").ok())
+    }
+
+    /// Like `save_path`, with the env var's value passed in
+    fn resolve_save_path(&self, env: Option<String>) -> String {
+        self.custom_save_path(env).unwrap_or_else(|| SaveSlot::in_dir(DEFAULT_SAVE_DIR, 1).path.to_string_lossy().into_owned())
+    }
+
+    /// The path from `--save` or the env var, `None` when the game uses the default slot
+    fn custom_save_path(&self, env: Option<String>) -> Option<String> {
+        self.save_path.clone().or(env)
+    }
+
+    /// Colors are disabled by `--no-color` or a non-empty `NO_COLOR` env var
+    pub fn should_colorize(&self) -> bool {
+        self.colorize_with(std::env::var_os("NO_COLOR"))
+    }
+
+    /// Like `should_colorize`, with the env var's value passed in
+    fn colorize_with(&self, no_color_env: Option<OsString>) -> bool {
+        let no_color_env = no_color_env.is_some_and(|value| !value.is_empty());
+        !no_color_env && !self.no_color
+    }
+}
+
+fn parse_difficulty(level: &str) -> Option<Difficulty> {
+    Difficulty::iter().find(|difficulty| difficulty.to_string().eq_ignore_ascii_case(level))
+}
+
+/// The crate version along with the build target and profile
+pub fn version_info() -> String {
+    let profile = if cfg!(debug_assertions) { "debug" } else { "release" };
+    format!("cli_farm {} ({} {}, {profile})", env!("CARGO_PKG_VERSION"), std::env::consts::OS, std::env::consts::ARCH)
 }
 
 pub fn run() {
+    run_with(Options::from_env())
+}
+
+pub fn run_with(options: Options) {
+    colored::control::set_override(options.should_colorize());
+    // The default save moved to slot 1, so a save.json from before slots is copied there first
+    let migrated = match options.custom_save_path(std::env::var("CLI_FARM_SAVE").ok()) {
+        Some(_) => Ok(None),
+        None => slots::migrate_legacy_save(DEFAULT_SAVE_DIR),
+    };
+    let save_path = options.save_path();
+    if options.status {
+        print_status_line(&save_path);
+        return
+    }
+    if let Some(dir) = options.leaderboard {
+        match Leaderboard::from_dir(&dir) {
+            Ok(leaderboard) if leaderboard.entries.is_empty() => println!("No saves found in {dir}"),
+            Ok(leaderboard) => println!("{}", leaderboard.render()),
+            Err(e) => println!("{e}"),
+        }
+        return
+    }
+    // Prompts read keys in raw mode where Ctrl-C isn't a signal, this covers the rest of the time.
+    // A second Ctrl-C before the first is handled exits right away, e.g. while blocked on piped input
+    let sigint = signal_hook::consts::SIGINT;
+    let registered = signal_hook::flag::register_conditional_shutdown(sigint, 130, Arc::clone(&INTERRUPTED))
+        .and_then(|_| signal_hook::flag::register(sigint, Arc::clone(&INTERRUPTED)));
+    if registered.is_err() {
+        println!("Unable to handle Ctrl-C, progress will be lost if you use it");
+    }
+    let config = match GameConfig::load(CONFIG_PATH) {
+        Ok(config) => Arc::new(config),
+        Err(e) => {
+            println!("{e}, using the default game balance");
+            wait();
+            Arc::default()
+        },
+    };
     print!("{esc}[2J{esc}[1;1H", esc = 27 as char);
     print_header(None);
+    match migrated {
+        Ok(Some(slot)) => println!("Your {} was copied to {}", slots::LEGACY_SAVE_NAME, slot.path.display()),
+        Ok(None) => (),
+        Err(e) => println!("Couldn't copy {} to slot 1: {e}", slots::LEGACY_SAVE_NAME),
+    }
     println!("{}: New game", "1".bold());
     println!("{}: Load game", "2".bold());
-    let mut farm = if input(2) == 1 {
-        println!("Starting new game...");
-        println!("Enter your name:");
-        let name = loop {
-            print!("> ");
-            std::io::Write::flush(&mut std::io::stdout()).unwrap();
-            let mut input = String::new();
-            match std::io::stdin().read_line(&mut input) {
-                Ok(_) => (),
-                Err(_) => {println!("Unable to read input"); continue},
-            };
-            break input.trim().to_string()
-        };
-        let farm = Farm::new(name);
-        println!("New game started");
-        farm
+    let choice = input(2);
+    if INTERRUPTED.load(Ordering::SeqCst) || STDIN_CLOSED.load(Ordering::SeqCst) { return }
+    let mut farm = if choice == 1 {
+        new_game(options.difficulty, &config)
     } else {
         println!("Loading game...");
-        let farm = Farm::load_from_path("save.json".to_string());
-        println!("Game loaded");
-        farm
+        load_or_new_game(&save_path, &config, || new_game(options.difficulty, &config))
     };
+    // Nothing has happened on the farm yet, so there's nothing to save
+    if INTERRUPTED.load(Ordering::SeqCst) { return }
+    let mut notifications: VecDeque<String> = VecDeque::new();
+    let mut field_sort = FieldSort::default();
+    // The farm from before entering sandbox mode, restored if the sandbox is discarded
+    let mut sandbox_original: Option<Farm> = None;
     
     loop {
+        if STDIN_CLOSED.load(Ordering::SeqCst) {
+            println!("Input closed, quitting without saving");
+            break
+        }
+        if INTERRUPTED.load(Ordering::SeqCst) {
+            if let Some(original) = sandbox_original.take() { farm = original }
+            save_on_interrupt(&mut farm, &save_path, confirm);
+            break
+        }
         print!("{esc}[2J{esc}[1;1H", esc = 27 as char);
-        print_header(Some(&farm.name));
-        println!("Balance: {}", format_money(farm.money));
+        let summary = farm.summary();
+        print_header(Some(&summary.name));
+        if farm.is_sandbox {
+            println!("{}", "Sandbox mode, changes won't be saved unless you keep them".bold().bright_yellow());
+        }
+        println!("Balance: {} ({}/s)", format_money(summary.money), format_money(summary.earnings_rate));
+        println!("Ready to harvest: {}/{}", summary.ready_count.to_string().bold().bright_magenta(), summary.field_count);
+        let now = util::timestamp();
+        let attention = farm.attention_list(now).len();
+        if attention > 0 {
+            println!("{} {} fields need attention", "!".bold().bright_yellow(), attention);
+        }
+        if let Some(remaining) = farm.time_until_next_ready(now).filter(|remaining| *remaining > 0) {
+            println!("Next harvest in {}", util::format_duration_ms(remaining).bold().bright_magenta());
+        }
+        let season = farm.current_season(now);
+        let in_season = Crop::iter().filter(|c| c.season() == season).map(|c| c.to_string()).collect::<Vec<String>>();
+        println!("Season: {} ({} in season)", season.to_string().bold(), in_season.join(", "));
+        if let Some(event) = farm.active_market_event(now) {
+            println!("{} harvests pay {:.0}% for {}", "Market crash!".bold().red(), event.multiplier * 100., util::format_duration_ms(event.ends_at - now));
+        }
+        if let Some(prompt) = farm.tutorial_prompt() {
+            println!("{} {}", "Tutorial:".bold().bright_yellow(), prompt);
+        } else if let Some(recommendation) = farm.best_investment(now) {
+            println!("{} {}", "Tip:".bold().bright_cyan(), recommendation);
+        }
+        for event in farm.tick(util::timestamp()) {
+            if farm.notify_on_ready && matches!(event, GameEvent::FieldReady { .. }) { print!("\x07") }
+            notifications.push_back(event.to_string());
+            if notifications.len() > MAX_NOTIFICATIONS { notifications.pop_front(); }
+        }
+        if farm.config().autosave_interval_ms().is_some_and(|interval| farm.should_autosave(now, interval)) {
+            farm.save_to_path(save_path.clone());
+        }
+        for notification in notifications.iter() {
+            println!("{} {}", "!".bold().bright_yellow(), notification);
+        }
         print_menu();
-        match input(8) {
+        let choice = match poll_input(Duration::from_secs(1)) {
+            Some(choice) if (choice as usize) < lang::MENU.len() => choice,
+            _ => continue,
+        };
+        let snapshot = farm.snapshot();
+        match choice {
             0 => {
-                println!("Do you want to save the game?\n{}: Back\n{}: Yes\n{}: No", "0".bold(), "1".bold(), "2".bold());
-                let input = input(2);
-                if input == 0 { continue }
-                if input == 1 {
+                if !confirm("Do you want to exit?") { continue }
+                if let Some(original) = sandbox_original.take() {
+                    println!("Leaving sandbox mode, its changes are discarded");
+                    farm = original;
+                }
+                if confirm("Do you want to save the game?") {
                     println!("Saving game...");
                     wait();
-                    farm.save_to_path("save.json".to_string());
+                    farm.save_to_path(save_path.clone());
                     println!("Game saved");
                 }
                 println!("Goodbye!");
                 break
             },
             1 => {
-                println!("{}", "Your farm (enter any number to go back):".bold().underline());
-                print_farm(&farm);
-                let _input = input(u32::MAX);
+                loop {
+                    println!("{}", format!("Your farm, sorted by {}:", field_sort.to_string().to_lowercase()).bold().underline());
+                    print_farm(&farm, field_sort);
+                    println!("{}: Back", "0".bold());
+                    println!("{}: Sort by {}", "1".bold(), field_sort.next().to_string().to_lowercase());
+                    if input(1) == 0 { break }
+                    field_sort = field_sort.next();
+                }
                 continue 
             },
             2 => {
@@ -76,12 +281,15 @@ pub fn run() {
                     println!("No fields to plant");
                 } else {
                     println!("{}", "Pick a field to plant".bold().underline());
-                    print_fields(&farm);
+                    print_fields(&farm, field_sort);
                     let input = input(farm.fields.len() as u32);
                     if input == 0 { continue }
                     let id = input - 1;
                     match farm.plant_field(id) {
-                        Ok(_) => println!("Field planted, it will be ready in {}s", format!("{}", farm.fields[id as usize].time_to_farm(util::timestamp())/1000).bold().bright_magenta()),
+                        Ok(_) => {
+                            farm.push_undo(snapshot);
+                            println!("Field planted, it will be ready in {}", util::format_duration_ms(farm.fields[id as usize].time_to_farm(util::timestamp(), farm.config())).bold().bright_magenta())
+                        },
                         Err(e) => println!("{}", e),
                     }
                 }
@@ -92,38 +300,49 @@ pub fn run() {
                     println!("No fields to farm");
                 } else {
                     println!("{}", "Pick a field to farm".bold().underline());
-                    print_fields(&farm);
+                    print_fields(&farm, field_sort);
                     let input = input(farm.fields.len() as u32);
                     if input == 0 { continue }
                     let id = input - 1;
-                    match farm.farm_field(id) {
-                        Ok(payout) => println!("Field farmed, you received ${payout:.2}"),
+                    match farm.harvest(id, util::timestamp()) {
+                        Ok(result) => {
+                            farm.push_undo(snapshot);
+                            println!("{} field farmed, you received {}, your balance is now {}", result.crop, format_money(result.payout), format_money(result.balance));
+                            if result.replanted { println!("Field replanted, it starts growing once the soil has rested") }
+                        },
                         Err(e) => println!("{}", e),
                     }
                 }
                 wait()
             },
             4 => {
-                print_shop();
-                let input = input(Crop::iter().count() as u32);
-                if input == 0 { continue }
-                let id = input - 1;
-                let crop = Crop::iter().nth(id as usize).unwrap();
-                match farm.buy_field(crop) {
-                    Ok(_) => println!("Field bought"),
+                if farm.is_full() {
+                    println!("Your farm is full, sell a field to make room");
+                    wait();
+                    continue
+                }
+                print_shop(&farm);
+                let crop_choice = input(Crop::iter().count() as u32);
+                if crop_choice == 0 { continue }
+                let Some(crop) = Crop::from_index(crop_choice as usize - 1) else { continue };
+                let free_slots = farm.config().max_fields().saturating_sub(farm.field_count());
+                println!("How many fields? (0 to go back, up to {free_slots})");
+                let count = input(free_slots.max(1) as u32);
+                if count == 0 { continue }
+                match farm.buy_fields(crop, count as usize, util::timestamp()) {
+                    Ok(bought) => {
+                        farm.push_undo(snapshot);
+                        if bought.len() == 1 { println!("Field bought") } else { println!("{} fields bought", bought.len()) }
+                    },
                     Err(e) => println!("{}", e),
                 }
                 wait()
             },
             5 => {
-                println!("{}", "Pick a field to level up".bold().underline());
-                print_fields(&farm);
-                let input = input(farm.fields.len() as u32);
-                if input == 0 { continue }
-                let id = input - 1;
-                match farm.level_up_field(id) {
-                    Ok(_) => println!("Field leveled up"),
-                    Err(e) => println!("{}", e),
+                match level_up_flow(&mut farm, field_sort, input) {
+                    Some(true) => farm.push_undo(snapshot),
+                    Some(false) => (),
+                    None => continue,
                 }
                 wait()
             },
@@ -132,29 +351,356 @@ pub fn run() {
                     println!("No fields to sell");
                 } else {
                     println!("{}", "Pick a field to sell".bold().underline());
-                    print_fields(&farm);
+                    print_sell_fields(&farm, field_sort);
                     let input = input(farm.fields.len() as u32);
                     if input == 0 { continue }
                     let id = input - 1;
+                    if !confirm("Are you sure you want to sell this field?") { continue }
                     match farm.sell_field(id) {
-                        Ok(price) => println!("Field sold, you received {}", format_money(price)),
+                        Ok(price) => {
+                            farm.push_undo(snapshot);
+                            println!("Field sold, you received {}", format_money(price))
+                        },
                         Err(e) => println!("{}", e),
                     }
                 }
                 wait()
             }
             7 => {
+                if farm.is_sandbox {
+                    println!("Sandbox changes can't be saved, keep them from the sandbox menu first");
+                    wait();
+                    continue
+                }
                 println!("Saving game...");
                 thread::sleep(Duration::from_secs(2));
-                farm.save_to_path("save.json".to_string());
+                farm.save_to_path(save_path.clone());
                 println!("Game saved");
                 wait()
             },
             8 => {
+                if !confirm("Loading discards unsaved progress, continue?") { continue }
                 println!("Loading game...");
                 thread::sleep(Duration::from_secs(2));
-                farm = Farm::load_from_path("save.json".to_string());
-                println!("Game loaded");
+                match Farm::read_from_path(&save_path) {
+                    Ok(loaded) => {
+                        farm = loaded;
+                        farm.set_config(config.clone());
+                        sandbox_original = None;
+                        println!("Game loaded");
+                        report_issues(&farm);
+                    },
+                    Err(e) => println!("{e}"),
+                }
+                wait()
+            },
+            9 => {
+                println!("{}", "Stats (enter any number to go back):".bold().underline());
+                print_stats(&farm);
+                let _input = input(u32::MAX);
+                continue
+            },
+            10 => {
+                println!("Enter the new name:");
+                match farm.rename(input_text()) {
+                    Ok(_) => {
+                        farm.push_undo(snapshot);
+                        println!("Farm renamed")
+                    },
+                    Err(e) => println!("{}", e),
+                }
+                wait()
+            },
+            11 => {
+                match farm.claim_daily_bonus(util::timestamp()) {
+                    Ok(bonus) => {
+                        farm.push_undo(snapshot);
+                        println!("You received a daily bonus of {}", format_money(bonus))
+                    },
+                    Err(e) => println!("{}", e),
+                }
+                wait()
+            },
+            12 => {
+                if farm.fields.is_empty() {
+                    println!("No fields to insure");
+                } else {
+                    println!("{}", "Pick a field to insure".bold().underline());
+                    print_fields(&farm, field_sort);
+                    let input = input(farm.fields.len() as u32);
+                    if input == 0 { continue }
+                    let id = input - 1;
+                    let price = farm.insurance_price(&farm.fields[id as usize]);
+                    match farm.buy_insurance(id) {
+                        Ok(_) => {
+                            farm.push_undo(snapshot);
+                            println!("Field insured for {}, a storm destroying its crop will pay out its earnings", format_money(price))
+                        },
+                        Err(e) => println!("{}", e),
+                    }
+                }
+                wait()
+            },
+            13 => {
+                match farm.undo() {
+                    Ok(()) => println!("Last action undone, {} more can be undone", farm.undo_count()),
+                    Err(e) => println!("{}", e),
+                }
+                wait()
+            },
+            14 => {
+                println!("Enter a file name (leave empty for {DEFAULT_CSV_PATH}):");
+                let path = match input_text() {
+                    path if path.is_empty() => DEFAULT_CSV_PATH.to_string(),
+                    path => path,
+                };
+                match std::fs::write(&path, farm.export_csv()) {
+                    Ok(_) => println!("Fields exported to {path}"),
+                    Err(e) => println!("Unable to export fields: {e}"),
+                }
+                wait()
+            },
+            15 => {
+                if !confirm("Are you sure you want to sell your cheapest empty field?") { continue }
+                match farm.sell_cheapest_field() {
+                    Ok(price) => {
+                        farm.push_undo(snapshot);
+                        println!("Field sold, you received {}", format_money(price))
+                    },
+                    Err(e) => println!("{}", e),
+                }
+                wait()
+            },
+            16 => {
+                if farm.fields.is_empty() {
+                    println!("No fields to toggle");
+                } else {
+                    println!("{}", "Pick a field to toggle auto-replant".bold().underline());
+                    print_fields(&farm, field_sort);
+                    let input = input(farm.fields.len() as u32);
+                    if input == 0 { continue }
+                    match farm.toggle_auto_replant(input - 1) {
+                        Ok(true) => println!("Field will be replanted after harvesting"),
+                        Ok(false) => println!("Field will no longer be replanted after harvesting"),
+                        Err(e) => println!("{}", e),
+                    }
+                }
+                wait()
+            },
+            17 => {
+                if farm.fields.is_empty() {
+                    println!("No fields to convert");
+                } else {
+                    println!("{}", "Pick a field to convert".bold().underline());
+                    print_fields(&farm, field_sort);
+                    let field_choice = input(farm.fields.len() as u32);
+                    if field_choice == 0 { continue }
+                    println!("{}", "Pick the new crop".bold().underline());
+                    let crops = Farm::available_crops();
+                    println!("{}: Back", "0".bold());
+                    for (i, crop) in crops.iter().enumerate() {
+                        println!("{}: {} for {}", (i + 1).to_string().bold(), crop, format_money(farm.conversion_price(*crop)));
+                    }
+                    let crop_choice = input(crops.len() as u32);
+                    if crop_choice == 0 { continue }
+                    match farm.convert_field(field_choice - 1, crops[crop_choice as usize - 1]) {
+                        Ok(price) => {
+                            farm.push_undo(snapshot);
+                            println!("Field converted for {}", format_money(price))
+                        },
+                        Err(e) => println!("{}", e),
+                    }
+                }
+                wait()
+            },
+            18 => {
+                if farm.fields.is_empty() {
+                    println!("No fields to build a greenhouse on");
+                } else {
+                    println!("{}", "Pick a field to build a greenhouse on".bold().underline());
+                    print_fields(&farm, field_sort);
+                    let input = input(farm.fields.len() as u32);
+                    if input == 0 { continue }
+                    let id = input - 1;
+                    let price = farm.greenhouse_price(&farm.fields[id as usize]);
+                    match farm.buy_greenhouse(id) {
+                        Ok(_) => {
+                            farm.push_undo(snapshot);
+                            println!("Greenhouse built for {}", format_money(price))
+                        },
+                        Err(e) => println!("{}", e),
+                    }
+                }
+                wait()
+            },
+            19 => {
+                println!("{}", format!("Pick a skill to unlock, you have {} XP", farm.xp).bold().underline());
+                let skills = Skill::iter().collect::<Vec<Skill>>();
+                println!("{}: Back", "0".bold());
+                for (i, skill) in skills.iter().enumerate() {
+                    let cost = if farm.skills.has(*skill) { "unlocked".bright_green() } else { format!("{} XP", skill.xp_cost()).bold() };
+                    println!("{}: {} ({}), {}", (i + 1).to_string().bold(), skill, skill.description(), cost);
+                }
+                let input = input(skills.len() as u32);
+                if input == 0 { continue }
+                match farm.unlock_skill(skills[input as usize - 1]) {
+                    Ok(_) => {
+                        farm.push_undo(snapshot);
+                        println!("Skill unlocked")
+                    },
+                    Err(e) => println!("{}", e),
+                }
+                wait()
+            },
+            20 => {
+                if farm.tutorial_step.is_none() {
+                    println!("The tutorial is already over");
+                } else {
+                    farm.skip_tutorial();
+                    farm.push_undo(snapshot);
+                    println!("Tutorial skipped");
+                }
+                wait()
+            },
+            21 => {
+                println!("{}", "Pick a crop to research".bold().underline());
+                let crops = Farm::available_crops();
+                println!("{}: Back", "0".bold());
+                for (i, crop) in crops.iter().enumerate() {
+                    println!("{}: {}, max level {} for {}", (i + 1).to_string().bold(), crop, farm.max_level(*crop).to_string().red().bold(), format_money(farm.research_price(*crop)));
+                }
+                let input = input(crops.len() as u32);
+                if input == 0 { continue }
+                let crop = crops[input as usize - 1];
+                match farm.research_max_level(crop) {
+                    Ok(_) => {
+                        farm.push_undo(snapshot);
+                        println!("{} fields can now reach level {}", crop, farm.max_level(crop))
+                    },
+                    Err(e) => println!("{}", e),
+                }
+                wait()
+            },
+            22 => {
+                if farm.fields.is_empty() {
+                    println!("No fields to clear");
+                } else {
+                    println!("{}", "Pick a field to clear, the crop on it is lost".bold().underline());
+                    print_fields(&farm, field_sort);
+                    let input = input(farm.fields.len() as u32);
+                    if input == 0 { continue }
+                    if !confirm("Are you sure you want to abandon this crop?") { continue }
+                    match farm.clear_field(input - 1) {
+                        Ok(_) => {
+                            farm.push_undo(snapshot);
+                            println!("Field cleared")
+                        },
+                        Err(e) => println!("{}", e),
+                    }
+                }
+                wait()
+            },
+            23 => {
+                let summary = farm.level_up_all();
+                if !summary.leveled.is_empty() { farm.push_undo(snapshot) }
+                println!("Leveled up {} fields for {}", summary.leveled.len(), format_money(summary.cost));
+                if !summary.maxed.is_empty() { println!("{} fields are at max level", summary.maxed.len()) }
+                if !summary.too_expensive.is_empty() { println!("{} fields were too expensive", summary.too_expensive.len()) }
+                wait()
+            },
+            24 => {
+                if farm.toggle_pause_growth_offline() {
+                    println!("Fields now only grow while the game is running");
+                } else {
+                    println!("Fields now keep growing while the game is closed");
+                }
+                farm.push_undo(snapshot);
+                wait()
+            },
+            25 => {
+                println!("{}", "Pick a crop to buy seeds for".bold().underline());
+                let crops = Farm::available_crops();
+                println!("{}: Back", "0".bold());
+                for (i, crop) in crops.iter().enumerate() {
+                    println!("{}: {} seeds for {} each, {} owned", (i + 1).to_string().bold(), crop, format_money(farm.planting_price(*crop)), farm.seed_count(*crop));
+                }
+                let crop_choice = input(crops.len() as u32);
+                if crop_choice == 0 { continue }
+                let crop = crops[crop_choice as usize - 1];
+                println!("How many seeds? (0 to go back)");
+                let count = input(u32::MAX);
+                if count == 0 { continue }
+                match farm.buy_seeds(crop, count) {
+                    Ok(cost) => {
+                        farm.push_undo(snapshot);
+                        println!("Bought {count} {crop} seeds for {}", format_money(cost));
+                    },
+                    Err(e) => println!("{}", e),
+                }
+                wait()
+            },
+            26 => {
+                match sandbox_original.take() {
+                    None => {
+                        if !confirm("Experiment in a sandbox? You can keep or discard the changes later") { continue }
+                        let sandbox = farm.sandbox();
+                        sandbox_original = Some(std::mem::replace(&mut farm, sandbox));
+                        println!("Sandbox started");
+                    },
+                    Some(original) => {
+                        println!("{}: Back", "0".bold());
+                        println!("{}: Keep sandbox changes", "1".bold());
+                        println!("{}: Discard sandbox changes", "2".bold());
+                        match input(2) {
+                            1 => {
+                                farm.commit_sandbox();
+                                println!("Sandbox changes kept");
+                            },
+                            2 => {
+                                farm = original;
+                                println!("Sandbox changes discarded");
+                            },
+                            _ => {
+                                sandbox_original = Some(original);
+                                continue
+                            },
+                        }
+                    },
+                }
+                wait()
+            },
+            27 => {
+                hotkey_mode(&mut farm, &save_path);
+                continue
+            },
+            28 => {
+                if farm.fields.is_empty() {
+                    println!("No fields to rest");
+                } else {
+                    println!("{}", "Pick an empty field to rest, restoring its soil".bold().underline());
+                    println!("{}: Back", "0".bold());
+                    for (i, field) in farm.fields.iter().enumerate() {
+                        println!("{}: {} field, soil health {}", (i + 1).to_string().bold(), field.crop, format!("{:.0}%", field.soil_health() * 100.).bold());
+                    }
+                    let input = input(farm.fields.len() as u32);
+                    if input == 0 { continue }
+                    match farm.rest_field(input - 1) {
+                        Ok(_) => {
+                            farm.push_undo(snapshot);
+                            println!("Field is resting, it can be planted again in {}", util::format_duration_ms(farm.fields[input as usize - 1].grow_time(farm.config())).bold().bright_magenta());
+                        },
+                        Err(e) => println!("{}", e),
+                    }
+                }
+                wait()
+            },
+            29 => {
+                if farm.toggle_notify_on_ready() {
+                    println!("The bell will ring when a field is ready");
+                } else {
+                    println!("The ready bell is off");
+                }
+                farm.push_undo(snapshot);
                 wait()
             },
             _ => unreachable!(),
@@ -163,89 +709,403 @@ pub fn run() {
     print!("{esc}[2J{esc}[1;1H", esc = 27 as char);
 }
 
+/// Prints any problems found in the farm, returns whether there were any
+fn report_issues(farm: &Farm) -> bool {
+    match farm.validate() {
+        Ok(()) => false,
+        Err(issues) => {
+            println!("{}", "The save file has problems:".bold().red());
+            for issue in issues {
+                println!("  {issue}");
+            }
+            true
+        },
+    }
+}
+
+/// Lets the player level up the field `pick` chooses, `pick` is not asked when there are no fields.
+/// Returns whether a field was leveled up, `None` if the player went back to the menu
+fn level_up_flow(farm: &mut Farm, sort: FieldSort, pick: impl FnOnce(u32) -> u32) -> Option<bool> {
+    if farm.fields.is_empty() {
+        println!("No fields to level up");
+        return Some(false)
+    }
+    println!("{}", "Pick a field to level up".bold().underline());
+    print_level_up_fields(farm, sort);
+    let input = pick(farm.fields.len() as u32);
+    if input == 0 { return None }
+    match farm.level_up_field(input - 1) {
+        Ok(_) => {
+            println!("Field leveled up");
+            Some(true)
+        },
+        Err(e) => {
+            println!("{}", e);
+            Some(false)
+        },
+    }
+}
+
+/// Asks whether to save before quitting after Ctrl-C, a second Ctrl-C quits without saving.
+/// Returns whether the farm was saved
+fn save_on_interrupt(farm: &mut Farm, save_path: &str, confirm: impl FnOnce(&str) -> bool) -> bool {
+    INTERRUPTED.store(false, Ordering::SeqCst);
+    println!();
+    let save = confirm("Save before quitting?");
+    if save {
+        farm.save_to_path(save_path.to_string());
+        println!("Game saved");
+    }
+    println!("Goodbye!");
+    save
+}
+
+/// Runs actions on single keypresses until q or Esc: h harvests, p plants and l levels up
+/// every field, s saves
+fn hotkey_mode(farm: &mut Farm, save_path: &str) {
+    println!("{}", "Hotkey mode: h harvest all, p plant all, l level up all, s save, q quit".bold().underline());
+    while let Some(key) = read_hotkey() {
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => break,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                INTERRUPTED.store(true, Ordering::SeqCst);
+                break
+            },
+            KeyCode::Char('s') if farm.is_sandbox => println!("Sandbox changes can't be saved, keep them from the sandbox menu first"),
+            KeyCode::Char('s') => {
+                farm.save_to_path(save_path.to_string());
+                println!("Game saved");
+            },
+            _ => {
+                let snapshot = farm.snapshot();
+                match handle_hotkey(key, farm) {
+                    Some(event) => {
+                        farm.push_undo(snapshot);
+                        println!("{event}");
+                    },
+                    None => println!("Nothing to do"),
+                }
+            },
+        }
+    }
+}
+
+/// The next key pressed, or the first character of a line when stdin isn't a terminal.
+/// `None` once input is closed
+fn read_hotkey() -> Option<KeyEvent> {
+    if !std::io::stdin().is_terminal() {
+        let c = read_stdin_line()?.trim().chars().next().unwrap_or(' ');
+        return Some(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE))
+    }
+    let _raw_mode = RawMode::enable();
+    loop {
+        match event::read().ok()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => return Some(key),
+            _ => continue,
+        }
+    }
+}
+
+/// Runs the farm action bound to `key`, `None` if the key isn't bound or there was nothing to do
+pub fn handle_hotkey(key: KeyEvent, farm: &mut Farm) -> Option<GameEvent> {
+    match key.code {
+        KeyCode::Char('h') => {
+            let (fields, payout) = farm.harvest_all(util::timestamp());
+            (fields > 0).then_some(GameEvent::HarvestedAll { fields, payout })
+        },
+        KeyCode::Char('p') => {
+            let fields = farm.plant_all();
+            (fields > 0).then_some(GameEvent::PlantedAll { fields })
+        },
+        KeyCode::Char('l') => {
+            let summary = farm.level_up_all();
+            (!summary.leveled.is_empty()).then_some(GameEvent::LeveledUpAll { fields: summary.leveled.len(), cost: summary.cost })
+        },
+        _ => None,
+    }
+}
+
+/// Raw mode that is disabled again when dropped, so the terminal is restored even on panic
+struct RawMode;
+
+impl RawMode {
+    /// `None` if the terminal doesn't support raw mode, e.g. when input is piped
+    fn enable() -> Option<RawMode> {
+        enable_raw_mode().ok()?;
+        Some(RawMode)
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = disable_raw_mode();
+    }
+}
+
 fn wait() {
-    enable_raw_mode().unwrap();
+    let _raw_mode = std::io::stdin().is_terminal().then(RawMode::enable).flatten();
     thread::sleep(Duration::from_secs_f32(1.5));
-    disable_raw_mode().unwrap();
 }
 
 fn print_menu() {
-    println!(
-"
-Pick an option:
-{}: Exit
-{}: View farm
-{}: Plant field
-{}: Harvest field
-{}: Buy new field
-{}: Level up field
-{}: Sell field
-{}: Save game
-{}: Load game
-","0".bold(), "1".bold(), "2".bold(), "3".bold(), "4".bold(), "5".bold(), "6".bold(), "7".bold(), "8".bold()
-    )
-}
-
-fn print_farm(farm: &Farm) {
-    let field_string = farm.fields.iter().map(|f| 
+    println!("\nPick an option:");
+    for i in 0..lang::MENU.len() {
+        println!("{}: {}", i.to_string().bold(), lang::tr(Message::Menu(i)));
+    }
+    println!();
+}
+
+/// Asks for a name and difficulty, unless given with `--difficulty`, and starts a new farm
+fn new_game(difficulty: Option<Difficulty>, config: &Arc<GameConfig>) -> Farm {
+    println!("Starting new game...");
+    println!("Enter your name:");
+    let name = read_farm_name();
+    let difficulty = difficulty.unwrap_or_else(|| {
+        println!("Pick a difficulty:");
+        let difficulties = Difficulty::iter().collect::<Vec<Difficulty>>();
+        for (i, difficulty) in difficulties.iter().enumerate() {
+            println!("{}: {}", (i + 1).to_string().bold(), difficulty);
+        }
+        match input(difficulties.len() as u32) {
+            0 => Difficulty::default(),
+            choice => difficulties[choice as usize - 1],
+        }
+    });
+    let farm = Farm::new_with_config(name, difficulty, config.clone());
+    println!("New game started");
+    farm
+}
+
+/// Loads the save at `path`, or starts a farm with `new_game` when there is no save or it can't be read
+fn load_or_new_game(path: &str, config: &Arc<GameConfig>, new_game: impl FnOnce() -> Farm) -> Farm {
+    match Farm::read_from_path(path) {
+        Ok(mut farm) => {
+            farm.set_config(config.clone());
+            println!("Game loaded");
+            if report_issues(&farm) { wait() }
+            farm
+        },
+        Err(LoadError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => {
+            println!("No saved game found at {path}, starting a new game instead");
+            new_game()
+        },
+        Err(e) => {
+            println!("{e}, starting a new game instead");
+            new_game()
+        },
+    }
+}
+
+/// Prints the saved farm's status line for `--status`, for use in prompts and status bars
+fn print_status_line(save_path: &str) {
+    if !std::path::Path::new(save_path).exists() {
+        println!("No saved farm");
+        return
+    }
+    match Farm::read_from_path(save_path) {
+        Ok(mut farm) => {
+            if let Ok(config) = GameConfig::load(CONFIG_PATH) { farm.set_config(Arc::new(config)) }
+            println!("{}", farm.status_line(util::timestamp()))
+        },
+        Err(e) => println!("{e}"),
+    }
+}
+
+fn print_stats(farm: &Farm) {
+    println!("Play time: {}", util::format_duration_ms(farm.play_time().as_millis()).bold().bright_magenta());
+    println!("Fields: {}/{}", farm.field_count().to_string().bold(), farm.config().max_fields());
+    let groups = farm.fields_by_crop();
+    let crops = Crop::iter().filter_map(|crop| Some(format!("{crop} ({})", groups.get(&crop)?.len()))).collect::<Vec<String>>();
+    if !crops.is_empty() { println!("Crops: {}", crops.join(", ")) }
+    println!("Difficulty: {}", farm.difficulty.to_string().bold());
+    println!("XP: {}", farm.xp.to_string().bold());
+    println!("Net worth: {}", format_money(farm.net_worth()));
+    if farm.diversity_bonus() > 1. {
+        println!("You grow every crop, harvests pay {}", format!("{:.0}% more", (farm.diversity_bonus() - 1.) * 100.).bold().bright_green());
+    }
+    println!("Balance in an hour if you keep replanting: {}", format_money(farm.projected_balance(util::timestamp(), 60 * 60 * 1000, true)));
+    if let Some(multiplier) = farm.prestige_preview() {
+        println!("Prestiging now would give a {} earnings multiplier", format!("x{multiplier:.2}").bold().bright_magenta());
+    }
+}
+
+fn print_farm(farm: &Farm, sort: FieldSort) {
+    let field_string = farm.fields_sorted(sort, util::timestamp()).into_iter().map(|(i, f)| 
         if f.planted() {
-            format!("{} field, level {}, ready to harvest {}, earnings {} per harvest", f.crop, f.level.to_string().red().bold(), 
-            {
-                let time_to_farm = f.time_to_farm(util::timestamp());
-                if time_to_farm > 0 {
-                    format!("in {}s", time_to_farm/1000).bold().bright_magenta()
-                } else {
-                    "NOW".bold().bright_magenta()
-                }
-            }
-            , format_money(f.earnings()))
+            format!("{} {} field, level {}, ready to harvest {}, earnings {} per harvest", format!("{}.", i+1).bold(), f.crop, f.level_display(farm.max_level(f.crop)).red().bold(), 
+            readiness_style(&farm.describe_field(f, util::timestamp())), format_money(farm.field_earnings(f, util::timestamp())))
         } else {
-            format!("{} field, level {}, price to plant {}, earnings {} per harvest", f.crop, f.level.to_string().red().bold(), format_money(f.crop.get_planting_price()), format_money(f.earnings()))
-        }
+            format!("{} {} field, level {}, {} seeds left, earnings {} per harvest", format!("{}.", i+1).bold(), f.crop, f.level_display(farm.max_level(f.crop)).red().bold(), farm.seed_count(f.crop).to_string().bold(), format_money(farm.field_earnings(f, util::timestamp())))
+        } + &if farm.field_needs_attention(f, util::timestamp()) { format!(" {}", "!".bold().bright_yellow()) } else { String::new() }
     ).collect::<Vec<String>>().join("\n  ");
     println!("Fields: [\n  {}\n]", field_string)
 }
 
-fn print_shop() {
-    let fields_string = Farm::available_crops().iter().enumerate().map(|(i, c)| 
-        format!("{}: {} field for {}, earnings per harvest {}, max level {}", format!("{}", i+1).bold(), c, format_money(Field::calculate_price(*c)), format_money(c.payout()), c.get_max_level().to_string().red().bold())
-    ).collect::<Vec<String>>().join("\n");
+fn print_shop(farm: &Farm) {
+    let config = farm.config();
+    let crops = Farm::available_crops();
+    let rotation = farm.shop_rotation(util::timestamp());
+    let tiers_string = Tier::iter().filter_map(|tier| {
+        let fields_string = crops.iter().enumerate().filter(|(_, c)| c.tier() == tier).map(|(i, c)| 
+            format!("{}: {} field for {}, earnings per harvest {}, max level {}", format!("{}", i+1).bold(), c, format_money(farm.field_price(*c)), format_money(farm.prices().payout(*c)), config.max_level(*c).to_string().red().bold())
+            + &match c.prerequisite() {
+                _ if !rotation.contains(c) => format!(" {}", "(not sold today)".red()),
+                Some(required) if !farm.prerequisite_met(*c) => format!(" {}", format!("(locked, needs a max level {} field)", required.name()).red()),
+                _ => String::new(),
+            }
+        ).collect::<Vec<String>>();
+        if fields_string.is_empty() { return None }
+        Some(format!("{}\n{}", tier.to_string().underline(), fields_string.join("\n")))
+    }).collect::<Vec<String>>().join("\n");
     println!("{}", "Pick a field to buy:".bold().underline());
-    println!("{}{}\n{}", "0".bold(), ": Back", fields_string)
+    println!("{}: Back\n{}", "0".bold(), tiers_string)
 }
 
-fn print_fields(farm: &Farm) {
-    let fields_string = farm.fields.iter().enumerate().map(|(i, f)| 
-        if f.planted() {
+fn print_fields(farm: &Farm, sort: FieldSort) {
+    let now = util::timestamp();
+    let fields_string = farm.fields_sorted(sort, now).into_iter().map(|(i, f)| {
+        let view = farm.describe_field(f, now);
+        let description = if view.planted {
             format!("{}: {} field, level {}, ready to harvest {}, price to level up {}", 
                 format!("{}", i+1).bold(), 
-                f.crop, f.level.to_string().red().bold(), 
-                {
-                    let time_to_farm = f.time_to_farm(util::timestamp());
-                    if time_to_farm > 0 {
-                        format!("in {}s", time_to_farm/1000).bold().bright_magenta()
-                    } else {
-                        "NOW".bold().bright_magenta()
-                    }
-                }, 
-                format_money(f.level_up_price().unwrap_or(f64::INFINITY))
+                view.crop, f.level_display(farm.max_level(f.crop)).red().bold(), 
+                readiness_style(&view),
+                format_level_up_price(&view)
             )
         } else {
-            format!("{}: {} field, level {}, price to plant {}, price to level up {}", format!("{}", i+1).bold(), f.crop, f.level.to_string().red().bold() , format_money(f.crop.get_planting_price()), format_money(f.level_up_price().unwrap_or(f64::INFINITY)))
+            format!("{}: {} field, level {}, {} seeds left, price to level up {}", format!("{}", i+1).bold(), view.crop, f.level_display(farm.max_level(f.crop)).red().bold() , farm.seed_count(view.crop).to_string().bold(), format_level_up_price(&view))
+        };
+        description + if f.auto_replant { ", auto-replant" } else { "" }
+            + if f.grow_speed_bonus > 0. { ", greenhouse" } else { "" }
+    }).collect::<Vec<String>>().join("\n");
+    println!("{}: Back\n{}", "0".bold(), fields_string)
+}
+
+fn print_sell_fields(farm: &Farm, sort: FieldSort) {
+    let fields_string = farm.fields_sorted(sort, util::timestamp()).into_iter().map(|(i, f)| 
+        format!("{}: {} field, level {}, price to sell {}", 
+            format!("{}", i+1).bold(), 
+            f.crop, f.level_display(farm.max_level(f.crop)).red().bold(),
+            format_money(farm.describe_field(f, util::timestamp()).sell_value),
+        )
+    ).collect::<Vec<String>>().join("\n");
+    println!("{}: Back\n{}", "0".bold(), fields_string)
+}
+
+fn print_level_up_fields(farm: &Farm, sort: FieldSort) {
+    let fields_string = farm.fields_sorted(sort, util::timestamp()).into_iter().map(|(i, f)| 
+        format!("{}: {} field, level {}, price to level up {}, earnings {} per harvest (max {})", 
+            format!("{}", i+1).bold(), 
+            f.crop, f.level_display(farm.max_level(f.crop)).red().bold(),
+            format_level_up_price(&farm.describe_field(f, util::timestamp())),
+            format_money(farm.field_earnings(f, util::timestamp())),
+            format_money(farm.max_earnings(f)),
+        ) + &match farm.level_up_cost_to(f, farm.max_level(f.crop)) {
+            Ok(cost) => format!(", cost to max {}", format_money(cost)),
+            Err(_) => String::new(),
         }
     ).collect::<Vec<String>>().join("\n");
-    println!("{}{}\n{}", "0".bold(), ": Back", fields_string)
+    println!("{}: Back\n{}", "0".bold(), fields_string)
 }
 
-fn input(max: u32) -> u32 {
+fn confirm(prompt: &str) -> bool {
+    println!("{prompt} {}", "[y/n]".bold());
     loop {
-        print!("> ");
-        std::io::Write::flush(&mut std::io::stdout()).unwrap();
-        let mut input = String::new();
-        match std::io::stdin().read_line(&mut input) {
-            Ok(_) => (),
-            Err(_) => {println!("Unable to read input"); continue},
+        let answer = input_text();
+        if INTERRUPTED.load(Ordering::SeqCst) || STDIN_CLOSED.load(Ordering::SeqCst) { return false }
+        match parse_confirmation(&answer) {
+            Some(answer) => return answer,
+            None => println!("Please answer yes or no"),
+        }
+    }
+}
+
+fn parse_confirmation(answer: &str) -> Option<bool> {
+    match answer.trim().to_lowercase().as_str() {
+        "y" | "yes" => Some(true),
+        "n" | "no" => Some(false),
+        _ => None,
+    }
+}
+
+/// Reads a line from `reader`, `None` once it is exhausted
+fn read_line(reader: &mut impl BufRead) -> Option<String> {
+    loop {
+        let mut line = String::new();
+        match reader.read_line(&mut line) {
+            Ok(0) => return None,
+            Ok(_) => return Some(line),
+            Err(_) => println!("Unable to read input"),
+        }
+    }
+}
+
+/// Prompts for a line on stdin, `None` if stdin is closed or Ctrl-C was pressed
+fn read_stdin_line() -> Option<String> {
+    print!("> ");
+    std::io::Write::flush(&mut std::io::stdout()).unwrap();
+    if STDIN_CLOSED.load(Ordering::SeqCst) || INTERRUPTED.load(Ordering::SeqCst) { return None }
+    if std::io::stdin().is_terminal() {
+        if let Some(line) = read_terminal_line() { return line }
+    }
+    let line = read_line(&mut std::io::stdin().lock());
+    if line.is_none() { STDIN_CLOSED.store(true, Ordering::SeqCst) }
+    line
+}
+
+/// Reads a line key by key in raw mode, so Ctrl-C is seen right away instead of after enter.
+/// `Some(None)` on Ctrl-C and `None` if the terminal can't be read this way
+fn read_terminal_line() -> Option<Option<String>> {
+    let _raw_mode = RawMode::enable()?;
+    let mut line = String::new();
+    loop {
+        let key = match event::read().ok()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => key,
+            _ => continue,
         };
+        match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                INTERRUPTED.store(true, Ordering::SeqCst);
+                print!("\r\n");
+                return Some(None)
+            },
+            KeyCode::Char(c) => {
+                line.push(c);
+                print!("{c}");
+            },
+            KeyCode::Backspace if line.pop().is_some() => print!("\x08 \x08"),
+            KeyCode::Enter => {
+                print!("\r\n");
+                return Some(Some(line))
+            },
+            _ => (),
+        }
+        std::io::Write::flush(&mut std::io::stdout()).unwrap();
+    }
+}
+
+/// Prompts until a valid farm name is entered
+fn read_farm_name() -> String {
+    loop {
+        let name = input_text();
+        if INTERRUPTED.load(Ordering::SeqCst) || STDIN_CLOSED.load(Ordering::SeqCst) { return name }
+        match Farm::validate_name(&name) {
+            Ok(name) => return name,
+            Err(e) => println!("{e}, please try again"),
+        }
+    }
+}
+
+fn input_text() -> String {
+    let Some(input) = read_stdin_line() else { return String::new() };
+    if INTERRUPTED.load(Ordering::SeqCst) { return String::new() }
+    input.trim().to_string()
+}
+
+fn input(max: u32) -> u32 {
+    loop {
+        let Some(input) = read_stdin_line() else { return 0 };
+        if INTERRUPTED.load(Ordering::SeqCst) { return 0 }
 
         let input = input.trim().parse();
         let input: u32 = match input {
@@ -258,3 +1118,182 @@ fn input(max: u32) -> u32 {
         return input
     }
 }
+
+/// Like `input`, but returns `None` if nothing is typed within `timeout` so the caller can redraw.
+/// Once a key has been pressed it waits for enter.
+pub fn poll_input(timeout: Duration) -> Option<u32> {
+    let raw_mode = std::io::stdin().is_terminal().then(RawMode::enable).flatten();
+    let Some(raw_mode) = raw_mode else { return read_stdin_line()?.trim().parse().ok() };
+    print!("> ");
+    std::io::Write::flush(&mut std::io::stdout()).unwrap();
+    let mut buffer = String::new();
+    let input = loop {
+        if buffer.is_empty() && !event::poll(timeout).unwrap_or(false) { break None }
+        let key = match event::read() {
+            Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => key,
+            Ok(_) => continue,
+            Err(_) => break None,
+        };
+        match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                INTERRUPTED.store(true, Ordering::SeqCst);
+                break None
+            },
+            KeyCode::Char(c) if c.is_ascii_digit() => {
+                buffer.push(c);
+                print!("{c}");
+            },
+            KeyCode::Backspace if buffer.pop().is_some() => print!("\x08 \x08"),
+            KeyCode::Enter => break buffer.parse().ok(),
+            _ => (),
+        }
+        std::io::Write::flush(&mut std::io::stdout()).unwrap();
+    };
+    drop(raw_mode);
+    println!();
+    input
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn save_path_prefers_the_argument_then_the_env_var() {
+        let env = Some("env.json".to_string());
+        assert_eq!(Options::parse(args(&["--save", "arg.json"])).resolve_save_path(env.clone()), "arg.json");
+        assert_eq!(Options::parse(args(&[])).resolve_save_path(env.clone()), "env.json");
+        assert_eq!(Options::parse(args(&["--save"])).resolve_save_path(env), "env.json");
+        assert_eq!(Options::parse(args(&[])).resolve_save_path(None), "./slot1.json");
+    }
+
+    #[test]
+    fn options_map_flags_to_fields() {
+        let options = Options::parse(args(&["--save", "farm.json", "--no-color", "--difficulty", "HARD", "--status", "--bogus"]));
+        assert_eq!(options, Options {
+            save_path: Some("farm.json".to_string()),
+            no_color: true,
+            difficulty: Some(Difficulty::Hard),
+            status: true,
+            ..Options::default()
+        });
+        assert_eq!(Options::parse(args(&["--difficulty", "extreme"])).difficulty, None);
+        assert_eq!(Options::parse(args(&["--leaderboard"])).leaderboard.as_deref(), Some("."));
+        assert!(Options::parse(args(&["--version"])).version && Options::parse(args(&["-V"])).version);
+        assert!(Options::parse(args(&["--help"])).help && Options::parse(args(&["-h"])).help);
+    }
+
+    #[test]
+    fn level_up_flow_without_fields_returns_without_asking() {
+        let mut farm = Farm::new("Test".to_string());
+        assert_eq!(level_up_flow(&mut farm, FieldSort::default(), |_| panic!("asked for a field")), Some(false));
+    }
+
+    #[test]
+    fn level_up_flow_levels_the_picked_field() {
+        let mut farm = Farm::new("Test".to_string());
+        farm.money = Money::dollars(100);
+        farm.buy_field(Crop::Wheat, util::timestamp()).unwrap();
+        assert_eq!(level_up_flow(&mut farm, FieldSort::default(), |max| max), Some(true));
+        assert_eq!(farm.fields[0].level, 2);
+        assert_eq!(level_up_flow(&mut farm, FieldSort::default(), |_| 0), None);
+    }
+
+    #[test]
+    fn confirmation_accepts_yes_and_no_in_any_case() {
+        for answer in ["y", "Y", " Yes\n", "YES"] {
+            assert_eq!(parse_confirmation(answer), Some(true));
+        }
+        for answer in ["n", "N", "no", "No\n"] {
+            assert_eq!(parse_confirmation(answer), Some(false));
+        }
+        assert_eq!(parse_confirmation("maybe"), None);
+        assert_eq!(parse_confirmation(""), None);
+    }
+
+    #[test]
+    fn save_on_interrupt_saves_when_confirmed() {
+        let dir = std::env::temp_dir();
+        let saved = dir.join(format!("cli_farm-{}-interrupt-saved.json", std::process::id()));
+        let skipped = dir.join(format!("cli_farm-{}-interrupt-skipped.json", std::process::id()));
+        let mut farm = Farm::new("Test".to_string());
+        farm.buy_field(Crop::Wheat, util::timestamp()).unwrap();
+
+        assert!(save_on_interrupt(&mut farm, saved.to_str().unwrap(), |_| true));
+        assert_eq!(Farm::load_from_path(saved.to_str().unwrap().to_string()).fields.len(), 1);
+        assert!(!save_on_interrupt(&mut farm, skipped.to_str().unwrap(), |_| false));
+        assert!(!skipped.exists());
+    }
+
+    #[test]
+    fn colors_are_disabled_by_the_flag_or_the_env_var() {
+        assert!(Options::default().colorize_with(None));
+        assert!(Options::default().colorize_with(Some(OsString::new())));
+        assert!(!Options::parse(args(&["--no-color"])).colorize_with(None));
+        assert!(!Options::default().colorize_with(Some(OsString::from("1"))));
+    }
+
+    #[test]
+    fn read_line_stops_at_the_end_of_input() {
+        let mut input = "first\n".as_bytes();
+        assert_eq!(read_line(&mut input).as_deref(), Some("first\n"));
+        assert_eq!(read_line(&mut input), None);
+    }
+
+    #[test]
+    fn loading_without_a_save_starts_a_new_game() {
+        let dir = crate::testkit::temp_dir("load-or-new");
+        let path = dir.join("save.json").to_string_lossy().into_owned();
+        let config = Arc::new(GameConfig::default());
+        let new_farm = || Farm::new("New".to_string());
+
+        assert_eq!(load_or_new_game(&path, &config, new_farm).name, "New");
+        std::fs::write(&path, "{").unwrap();
+        assert_eq!(load_or_new_game(&path, &config, new_farm).name, "New");
+
+        let mut saved = Farm::new("Saved".to_string());
+        saved.save_to_path(path.clone());
+        assert_eq!(load_or_new_game(&path, &config, || panic!("started a new game")).name, "Saved");
+    }
+
+    #[test]
+    fn readiness_is_green_only_when_ready() {
+        let mut farm = Farm::new("Test".to_string());
+        farm.fields.push(crate::testkit::planted_field(Crop::Wheat, 0));
+        let now = util::timestamp();
+        let ready = readiness_style(&farm.describe_field(&farm.fields[0], now));
+        assert_eq!((&*ready, ready.fgcolor()), ("NOW", Some(colored::Color::BrightGreen)));
+
+        farm.fields[0].plant_timestamp = Some(now);
+        let growing = readiness_style(&farm.describe_field(&farm.fields[0], now));
+        assert!(growing.starts_with("in "));
+        assert_eq!(growing.fgcolor(), Some(colored::Color::BrightMagenta));
+
+        farm.fields[0].plant_timestamp = None;
+        let unplanted = readiness_style(&farm.describe_field(&farm.fields[0], now));
+        assert_eq!((&*unplanted, unplanted.fgcolor()), ("not planted", Some(colored::Color::BrightBlack)));
+    }
+
+    fn key(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn hotkeys_run_farm_actions() {
+        let mut farm = crate::testkit::farm_with_money(Money::dollars(1_000));
+        farm.buy_field(Crop::Wheat, util::timestamp()).unwrap();
+        assert_eq!(handle_hotkey(key('p'), &mut farm), None);
+        farm.buy_seeds(Crop::Wheat, 1).unwrap();
+        assert_eq!(handle_hotkey(key('p'), &mut farm), Some(GameEvent::PlantedAll { fields: 1 }));
+        assert_eq!(handle_hotkey(key('h'), &mut farm), None);
+
+        farm.fields[0].plant_timestamp = Some(0);
+        assert!(matches!(handle_hotkey(key('h'), &mut farm), Some(GameEvent::HarvestedAll { fields: 1, .. })));
+        assert_eq!(handle_hotkey(key('l'), &mut farm), Some(GameEvent::LeveledUpAll { fields: 1, cost: Money::from_cents(250) }));
+        assert_eq!(handle_hotkey(key('x'), &mut farm), None);
+    }
+}
@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+
+use serde::{Serialize, Deserialize};
+
+use crate::{farm::{Crop, Level, MAX_FIELDS}, money::Money, util::{self, GameError, Result}};
+
+const DEFAULT_AUTOSAVE_INTERVAL_SECS: u128 = 60;
+const DEFAULT_GROW_JITTER: f64 = 0.1;
+
+/// Overrides for a crop's built-in stats, unset values keep the default
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CropStats {
+    pub new_field_price: Option<Money>,
+    pub planting_price: Option<Money>,
+    pub max_level: Option<Level>,
+    pub grow_time_secs: Option<u128>,
+    pub payout: Option<Money>,
+}
+
+/// Game balance read from a JSON file at startup, passed to the farm with `Farm::new_with_config`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameConfig {
+    pub starting_money: Option<Money>,
+    pub max_fields: Option<usize>,
+    pub crops: HashMap<Crop, CropStats>,
+    /// Whether the game saves automatically, on by default
+    pub autosave: Option<bool>,
+    pub autosave_interval_secs: Option<u128>,
+    /// How far grow times may randomly stray, as a fraction of the grow time, 0 turns it off
+    pub grow_jitter: Option<f64>,
+}
+
+impl GameConfig {
+    /// Reads the config at `path`, a missing file gives the defaults
+    pub fn load(path: &str) -> Result<GameConfig> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(GameConfig::default()),
+            Err(_) => return Err(GameError::InvalidConfig),
+        };
+        serde_json::from_str(&contents).map_err(|_| GameError::InvalidConfig)
+    }
+
+    pub fn crop(&self, crop: Crop) -> Option<&CropStats> {
+        self.crops.get(&crop)
+    }
+
+    pub fn max_fields(&self) -> usize {
+        self.max_fields.unwrap_or(MAX_FIELDS)
+    }
+
+    pub fn new_field_price(&self, crop: Crop) -> Money {
+        self.crop(crop).and_then(|s| s.new_field_price).unwrap_or(crop.get_new_field_price())
+    }
+
+    pub fn planting_price(&self, crop: Crop) -> Money {
+        self.crop(crop).and_then(|s| s.planting_price).unwrap_or(crop.get_planting_price())
+    }
+
+    pub fn max_level(&self, crop: Crop) -> Level {
+        self.crop(crop).and_then(|s| s.max_level).unwrap_or(crop.get_max_level())
+    }
+
+    pub fn grow_time(&self, crop: Crop) -> u128 {
+        self.crop(crop).and_then(|s| s.grow_time_secs).map(util::seconds_to_millis).unwrap_or(crop.grow_time())
+    }
+
+    /// How long the soil rests after a harvest before it can be planted by hand
+    pub fn rest_time(&self, crop: Crop) -> u128 {
+        self.grow_time(crop) / 10
+    }
+
+    pub fn payout(&self, crop: Crop) -> Money {
+        self.crop(crop).and_then(|s| s.payout).unwrap_or(crop.payout())
+    }
+
+    pub fn next_level_price(&self, crop: Crop, level: Level) -> Money {
+        crop.next_level_price_from(self.planting_price(crop), level)
+    }
+
+    pub fn grow_jitter(&self) -> f64 {
+        self.grow_jitter.unwrap_or(DEFAULT_GROW_JITTER).clamp(0., 1.)
+    }
+
+    /// How often to autosave, `None` if autosaving is turned off
+    pub fn autosave_interval_ms(&self) -> Option<u128> {
+        if !self.autosave.unwrap_or(true) { return None }
+        Some(util::seconds_to_millis(self.autosave_interval_secs.unwrap_or(DEFAULT_AUTOSAVE_INTERVAL_SECS)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::PathBuf, sync::Arc};
+
+    use super::*;
+    use crate::farm::{Difficulty, Farm};
+
+    fn write_config(name: &str, contents: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("cli_farm-{}-{name}.json", std::process::id()));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn missing_file_gives_the_defaults() {
+        let config = GameConfig::load("no/such/config.json").unwrap();
+        assert_eq!(config.max_fields(), MAX_FIELDS);
+        assert_eq!(config.payout(Crop::Wheat), Crop::Wheat.payout());
+    }
+
+    #[test]
+    fn farm_uses_the_config_it_was_created_with() {
+        let path = write_config("config", r#"{ "max_fields": 3, "crops": { "Wheat": { "payout": 2.5 } } }"#);
+        let config = GameConfig::load(path.to_str().unwrap()).unwrap();
+        assert_eq!(config.payout(Crop::Wheat), Money::from_cents(250));
+        assert_eq!(config.payout(Crop::Potato), Crop::Potato.payout());
+
+        let mut farm = Farm::new_with_config("Test".to_string(), Difficulty::Normal, Arc::new(config));
+        farm.buy_field(Crop::Wheat, util::timestamp()).unwrap();
+        assert_eq!(farm.field_earnings(&farm.fields[0], util::timestamp()), Money::from_cents(375));
+        assert_eq!(Farm::new("Test".to_string()).config().max_fields(), MAX_FIELDS);
+    }
+
+    #[test]
+    fn malformed_file_is_an_error() {
+        let path = write_config("bad-config", "{ not json");
+        assert!(matches!(GameConfig::load(path.to_str().unwrap()), Err(GameError::InvalidConfig)));
+    }
+
+    #[test]
+    fn autosave_can_be_configured_or_turned_off() {
+        assert_eq!(GameConfig::default().autosave_interval_ms(), Some(util::seconds_to_millis(DEFAULT_AUTOSAVE_INTERVAL_SECS)));
+        let config: GameConfig = serde_json::from_str(r#"{ "autosave_interval_secs": 5 }"#).unwrap();
+        assert_eq!(config.autosave_interval_ms(), Some(5_000));
+        let config: GameConfig = serde_json::from_str(r#"{ "autosave": false, "autosave_interval_secs": 5 }"#).unwrap();
+        assert_eq!(config.autosave_interval_ms(), None);
+    }
+}
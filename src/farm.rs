@@ -1,16 +1,212 @@
-use std::fmt;
-use crossterm::style::Stylize;
+use std::{collections::{HashMap, HashSet, VecDeque}, fmt, sync::Arc, time::Duration};
+use colored::{Color, Colorize};
 use strum::IntoEnumIterator;
 use util::Result;
 
 use serde::{Serialize, Deserialize};
 
-use crate::util::{self, GameError};
+use crate::{config::GameConfig, logging, money::Money, pricing::{Pricing, PricingStrategy, Prices, STANDARD_PRICING_ID}, util::{self, GameError, LoadError}};
 
-type Money = f64;
-type Level = u8;
+pub type Level = u8;
 
-#[derive(Clone, Copy, Debug, strum::EnumIter, Serialize, Deserialize)]
+pub const MAX_FIELDS: usize = 10;
+pub const MAX_NAME_LENGTH: usize = 32;
+const DAILY_BONUS_MIN: u64 = 5;
+const DAILY_BONUS_MAX: u64 = 25;
+/// Insurance costs this fraction of the field's earnings
+const INSURANCE_RATE: f64 = 0.25;
+/// How often a storm can strike
+pub const STORM_INTERVAL_MS: u128 = 60 * 60 * 1000;
+/// Chance per interval of a storm destroying one planted field
+const STORM_CHANCE: f64 = 0.03;
+const DEFAULT_SELL_REFUND_RATE: f64 = 0.5;
+/// Converting a field costs this fraction of the new crop's field price
+const CONVERSION_RATE: f64 = 0.5;
+const XP_PER_HARVEST: u64 = 1;
+/// Earnings multiplier for crops harvested in their season
+const IN_SEASON_BONUS: f64 = 1.5;
+/// Actions kept in the replay log by `Farm::compact_save`
+const COMPACT_LOG_LENGTH: usize = 100;
+/// Stands in for crops in a save that this version doesn't know
+const UNKNOWN_CROP_PLACEHOLDER: Crop = Crop::Wheat;
+/// Price of the first research as a multiple of the field price
+const RESEARCH_RATE: f64 = 5.;
+const TUTORIAL_STEPS: u8 = 3;
+const CHEAP_UPGRADES_MULTIPLIER: f64 = 0.8;
+const GREEN_THUMB_MULTIPLIER: f64 = 0.9;
+/// How much a greenhouse cuts a field's grow time
+pub const GREENHOUSE_BONUS: f64 = 0.25;
+/// Price of a greenhouse as a fraction of the field price
+const GREENHOUSE_RATE: f64 = 2.;
+/// Net worth needed before a prestige pays off
+pub const PRESTIGE_THRESHOLD: Money = Money::dollars(100_000);
+/// How often the market can crash, and how long a crash lasts
+pub const MARKET_INTERVAL_MS: u128 = 60 * 60 * 1000;
+/// Chance of a crash per interval at normal volatility
+const MARKET_CRASH_CHANCE: f64 = 0.02;
+/// How often interest is paid on idle money
+pub const INTEREST_INTERVAL_MS: u128 = 60 * 60 * 1000;
+/// How often property tax is charged
+pub const TAX_INTERVAL_MS: u128 = util::DAY_MILLIS;
+/// Extra earnings for owning a field of every crop
+pub const DIVERSITY_BONUS: f64 = 0.1;
+/// Fraction of soil health lost with every harvest since the field was last rested
+pub const SOIL_DEPLETION: f64 = 0.02;
+/// Soil health never drops below this
+pub const MIN_SOIL_HEALTH: f64 = 0.5;
+/// How many actions can be undone
+pub const UNDO_HISTORY_LIMIT: usize = 10;
+/// How often prices rise by the inflation rate
+pub const INFLATION_INTERVAL_MS: u128 = util::DAY_MILLIS;
+
+fn default_sell_refund_rate() -> f64 {
+    DEFAULT_SELL_REFUND_RATE
+}
+
+fn default_pricing_strategy() -> String {
+    STANDARD_PRICING_ID.to_string()
+}
+
+fn default_multiplier() -> f64 {
+    1.
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, strum::EnumIter, Serialize, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub fn starting_money(&self) -> Money {
+        match self {
+            Difficulty::Easy => Money::dollars(50),
+            Difficulty::Normal => Money::dollars(20),
+            Difficulty::Hard => Money::dollars(10),
+        }
+    }
+
+    pub fn sell_refund_rate(&self) -> f64 {
+        match self {
+            Difficulty::Easy => 0.8,
+            Difficulty::Normal => DEFAULT_SELL_REFUND_RATE,
+            Difficulty::Hard => 0.3,
+        }
+    }
+
+    pub fn grow_time_multiplier(&self) -> f64 {
+        match self {
+            Difficulty::Easy => 0.75,
+            Difficulty::Normal => 1.,
+            Difficulty::Hard => 1.25,
+        }
+    }
+
+    /// How strongly market events swing prices
+    pub fn market_volatility(&self) -> f64 {
+        match self {
+            Difficulty::Easy => 0.5,
+            Difficulty::Normal => 1.,
+            Difficulty::Hard => 1.5,
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// Orders for listing fields, see `Farm::fields_sorted`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, strum::EnumIter)]
+pub enum FieldSort {
+    /// The order the fields were bought in
+    #[default]
+    Bought,
+    /// Soonest ready first, unplanted fields last
+    Readiness,
+    /// Highest earnings first
+    Earnings,
+    /// Highest level first
+    Level,
+    Crop,
+}
+
+impl FieldSort {
+    /// The sort after this one, wrapping around
+    pub fn next(&self) -> FieldSort {
+        let sorts = FieldSort::iter().collect::<Vec<FieldSort>>();
+        let index = sorts.iter().position(|sort| sort == self).unwrap();
+        sorts[(index + 1) % sorts.len()]
+    }
+}
+
+impl fmt::Display for FieldSort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldSort::Bought => write!(f, "Bought"),
+            FieldSort::Readiness => write!(f, "Readiness"),
+            FieldSort::Earnings => write!(f, "Earnings"),
+            FieldSort::Level => write!(f, "Level"),
+            FieldSort::Crop => write!(f, "Crop"),
+        }
+    }
+}
+
+/// A perk bought with XP, see `Farm::unlock_skill`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, strum::EnumIter, Serialize, Deserialize)]
+pub enum Skill {
+    /// Level ups cost 20% less
+    CheapUpgrades,
+    /// Fields grow 10% faster
+    GreenThumb,
+}
+
+impl Skill {
+    pub fn xp_cost(&self) -> u64 {
+        match self {
+            Skill::CheapUpgrades => 25,
+            Skill::GreenThumb => 50,
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Skill::CheapUpgrades => "Level ups cost 20% less",
+            Skill::GreenThumb => "Fields grow 10% faster",
+        }
+    }
+}
+
+impl fmt::Display for Skill {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Skill::CheapUpgrades => write!(f, "Cheap upgrades"),
+            Skill::GreenThumb => write!(f, "Green thumb"),
+        }
+    }
+}
+
+/// The skills a farm has unlocked
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Skills(HashSet<Skill>);
+
+impl Skills {
+    pub fn has(&self, skill: Skill) -> bool {
+        self.0.contains(&skill)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, strum::EnumIter, Serialize, Deserialize)]
 pub enum Crop {
     Wheat,
     Potato,
@@ -18,22 +214,71 @@ pub enum Crop {
     Tomato,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, strum::EnumIter)]
+pub enum Tier {
+    Basic,
+    Intermediate,
+    Advanced,
+}
+
+impl fmt::Display for Tier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Tier::Basic => "Basic",
+            Tier::Intermediate => "Intermediate",
+            Tier::Advanced => "Advanced",
+        };
+        write!(f, "{s}")
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, strum::EnumIter)]
+pub enum Season {
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+impl Season {
+    pub fn at(timestamp: u128) -> Season {
+        Season::iter().nth(util::season_index(timestamp)).unwrap()
+    }
+}
+
+impl fmt::Display for Season {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Season::Spring => "Spring",
+            Season::Summer => "Summer",
+            Season::Autumn => "Autumn",
+            Season::Winter => "Winter",
+        };
+        write!(f, "{s}")
+    }
+}
+
 impl Crop {
+    /// The crop at zero-based position `i` in shop order
+    pub fn from_index(i: usize) -> Option<Crop> {
+        Crop::iter().nth(i)
+    }
+
     pub fn get_new_field_price(&self) -> Money {
         match self {
-            Crop::Wheat => 10.,
-            Crop::Potato => 100.,
-            Crop::Carrot => 1000.,
-            Crop::Tomato => 10000.,
+            Crop::Wheat => Money::dollars(10),
+            Crop::Potato => Money::dollars(100),
+            Crop::Carrot => Money::dollars(1000),
+            Crop::Tomato => Money::dollars(10000),
         }
     }
 
     pub fn get_planting_price(&self) -> Money {
         match self {
-            Crop::Wheat => 1.,
-            Crop::Potato => 20.,
-            Crop::Carrot => 50.,
-            Crop::Tomato => 100.,
+            Crop::Wheat => Money::dollars(1),
+            Crop::Potato => Money::dollars(20),
+            Crop::Carrot => Money::dollars(50),
+            Crop::Tomato => Money::dollars(100),
         }
     }
 
@@ -62,184 +307,3619 @@ impl Crop {
 
     pub fn payout(&self) -> Money {
         match self {
-            Crop::Wheat => 1.,
-            Crop::Potato => 10.,
-            Crop::Carrot => 100.,
-            Crop::Tomato => 200.,
+            Crop::Wheat => Money::dollars(1),
+            Crop::Potato => Money::dollars(10),
+            Crop::Carrot => Money::dollars(100),
+            Crop::Tomato => Money::dollars(200),
+        }
+    }
+
+    /// Plain name without any styling, for saves, logs and exports.
+    /// Display adds colors so it shouldn't be used for anything that is stored.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Crop::Wheat => "Wheat",
+            Crop::Potato => "Potato",
+            Crop::Carrot => "Carrot",
+            Crop::Tomato => "Tomato",
+        }
+    }
+
+    pub fn tier(&self) -> Tier {
+        match self {
+            Crop::Wheat => Tier::Basic,
+            Crop::Potato => Tier::Intermediate,
+            Crop::Carrot => Tier::Advanced,
+            Crop::Tomato => Tier::Advanced,
+        }
+    }
+
+    /// The season the crop earns extra in
+    pub fn season(&self) -> Season {
+        match self {
+            Crop::Wheat => Season::Summer,
+            Crop::Potato => Season::Autumn,
+            Crop::Carrot => Season::Winter,
+            Crop::Tomato => Season::Spring,
+        }
+    }
+
+    pub fn season_multiplier(&self, season: Season) -> f64 {
+        if self.season() == season { IN_SEASON_BONUS } else { 1. }
+    }
+
+    /// The crop you need a max level field of before this one can be bought
+    pub fn prerequisite(&self) -> Option<Crop> {
+        match self {
+            Crop::Wheat => None,
+            Crop::Potato => Some(Crop::Wheat),
+            Crop::Carrot => Some(Crop::Potato),
+            Crop::Tomato => Some(Crop::Carrot),
+        }
+    }
+
+    pub fn icon(&self) -> char {
+        match self {
+            Crop::Wheat => '🌾',
+            Crop::Potato => '🥔',
+            Crop::Carrot => '🥕',
+            Crop::Tomato => '🍅',
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            Crop::Wheat => Color::Green,
+            Crop::Potato => Color::Yellow,
+            Crop::Carrot => Color::BrightYellow,
+            Crop::Tomato => Color::BrightRed,
         }
     }
 
     pub fn get_next_level_price(&self, level: Level) -> Money {
-        let base_price = self.get_planting_price() * 10.;
+        self.next_level_price_from(self.get_planting_price(), level)
+    }
+
+    /// The price of leveling up from `level` when planting costs `planting_price`
+    pub fn next_level_price_from(&self, planting_price: Money, level: Level) -> Money {
+        let base_price = planting_price * 10.;
         let level_multiplier = self.level_multiplier()/2.;
-        let price = base_price * (level_multiplier * level as f64);
-        price
+        base_price * (level_multiplier * level as f64)
     }
 }
 
 impl fmt::Display for Crop {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let s = match self {
-            Crop::Wheat => "Wheat".bold().dark_green(),
-            Crop::Potato => "Potato".bold().dark_yellow(),
-            Crop::Carrot => "Carrot".bold().yellow(),
-            Crop::Tomato => "Tomato".bold().red(),
-        };
-        write!(f, "{s}")
+        write!(f, "{}", self.name().bold().color(self.color()))
+    }
+}
+
+/// Identifies a field for its whole life, unlike its index which shifts when an earlier field is sold.
+/// 0 means not yet assigned.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct FieldId(pub u64);
+
+impl fmt::Display for FieldId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}", self.0)
     }
 }
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Field {
+    #[serde(default)]
+    pub id: FieldId,
     pub crop: Crop,
     pub level: Level,
     pub plant_timestamp: Option<u128>,
+    #[serde(default)]
+    pub insured: bool,
+    /// Scales the crop's grow time, set from the difficulty when bought
+    #[serde(default = "default_multiplier")]
+    pub grow_time_multiplier: f64,
+    /// Replant right after harvesting, the new planting starts growing once the soil has rested
+    #[serde(default)]
+    pub auto_replant: bool,
+    /// Whether `Farm::tick` has reported the current planting as ready
+    #[serde(default)]
+    ready_reported: bool,
+    /// The field can't be planted by hand before this timestamp
+    #[serde(default)]
+    pub rest_until: Option<u128>,
+    /// Fraction the grow time is cut by, from a greenhouse
+    #[serde(default)]
+    pub grow_speed_bonus: f64,
+    /// Random offset to the grow time of the current planting, so fields don't all finish at once
+    #[serde(default)]
+    pub grow_jitter_ms: i64,
+    /// How long the current planting has grown, as of the last tick or save
+    #[serde(default)]
+    pub elapsed_growth: u128,
+    /// Harvests since the field was bought or last rested, each one depletes the soil
+    #[serde(default)]
+    pub harvest_count: u32,
 }
 
 impl Field {
     pub fn new(crop: Crop) -> Field {
         Self {
+            id: FieldId::default(),
             crop,
             level: 1,
             plant_timestamp: None,
+            insured: false,
+            grow_time_multiplier: 1.,
+            auto_replant: false,
+            ready_reported: false,
+            rest_until: None,
+            grow_speed_bonus: 0.,
+            grow_jitter_ms: 0,
+            elapsed_growth: 0,
+            harvest_count: 0,
         }
     }
 
-    pub fn calculate_price(crop: Crop) -> Money {
-        crop.get_new_field_price()
+    pub fn calculate_price(crop: Crop, prices: Prices) -> Money {
+        prices.new_field_price(crop)
+    }
+
+    /// `max_level` is the crop's max level including research, see `Farm::max_level`
+    pub fn level_up_price(&self, prices: Prices, max_level: Level) -> Result<Money> {
+        if self.level >= max_level { return Err(GameError::MaxLevelReached) }
+        Ok(prices.level_up_price(self.crop, self.level))
+    }
+
+    /// What the field price and every level up bought so far add up to
+    pub fn invested(&self, prices: Prices) -> Money {
+        let levels = (1..self.level).map(|level| prices.level_up_price(self.crop, level)).sum();
+        Field::calculate_price(self.crop, prices) + levels
     }
 
-    pub fn level_up_price(&self) -> Result<Money> {
-        if self.level >= self.crop.get_max_level() { return Err(GameError::MaxLevelReached) }
-        Ok(self.crop.get_next_level_price(self.level))
+    /// The refund for selling the field, `refund_rate` of what was invested in it
+    pub fn sell_value(&self, prices: Prices, refund_rate: f64) -> Money {
+        self.invested(prices) * refund_rate
     }
 
-    pub fn level_up(&mut self) -> Result<()> {
-        if self.level >= self.crop.get_max_level() { return Err(GameError::MaxLevelReached) }
+    pub fn level_up(&mut self, max_level: Level) -> Result<()> {
+        if self.level >= max_level { return Err(GameError::MaxLevelReached) }
         self.level += 1;
         Ok(())
     }
 
+    /// The level as "level/max", never showing a level above the max
+    pub fn level_display(&self, max_level: Level) -> String {
+        format!("{}/{}", self.level.min(max_level), max_level)
+    }
+
     pub fn planted(&self) -> bool {
         self.plant_timestamp.is_some()
     }
 
     pub fn plant(&mut self, timestamp: u128) -> Result<()> {
+        if self.resting(timestamp) { return Err(GameError::SoilResting) }
+        self.plant_after_rest(timestamp)
+    }
+
+    /// Plants the field so it starts growing once the soil has rested, right away if it isn't resting
+    pub fn plant_after_rest(&mut self, now: u128) -> Result<()> {
         if self.planted() { return Err(GameError::AlreadyPlanted) }
-        self.plant_timestamp = Some(timestamp);
+        self.plant_timestamp = Some(self.rest_until.map_or(now, |until| until.max(now)));
+        self.ready_reported = false;
+        self.rest_until = None;
         Ok(())
     }
 
-    pub fn time_to_farm(&self, timestamp: u128) -> u128 {
-        self.crop.grow_time().checked_sub(timestamp - self.plant_timestamp.unwrap()).unwrap_or(0)
+    pub fn resting(&self, now: u128) -> bool {
+        self.rest_until.is_some_and(|until| now < until)
+    }
+
+    /// How long the field has been growing, `None` if it isn't planted
+    pub fn age(&self, now: u128) -> Option<u128> {
+        self.plant_timestamp.map(|planted| now.saturating_sub(planted))
+    }
+
+    /// The grow time before jitter
+    pub fn base_grow_time(&self, config: &GameConfig) -> u128 {
+        (config.grow_time(self.crop) as f64 * self.grow_time_multiplier * (1. - self.grow_speed_bonus)) as u128
+    }
+
+    pub fn grow_time(&self, config: &GameConfig) -> u128 {
+        self.base_grow_time(config).saturating_add_signed(self.grow_jitter_ms as i128)
+    }
+
+    /// Planted and done growing
+    pub fn is_ready(&self, now: u128, config: &GameConfig) -> bool {
+        self.planted() && self.time_to_farm(now, config) == 0
+    }
+
+    /// Whether the field on its own needs the player, which is when it's ready to harvest.
+    /// See `Farm::field_needs_attention` for empty fields that could be planted
+    pub fn needs_attention(&self, now: u128, config: &GameConfig) -> bool {
+        self.is_ready(now, config)
+    }
+
+    /// Time left until the field is ready, an auto-replanted field first waits out the soil's rest.
+    /// A planting further ahead than that is bad data, see `planted_in_future`, and reports the full grow time
+    pub fn time_to_farm(&self, timestamp: u128, config: &GameConfig) -> u128 {
+        let grow_time = self.grow_time(config);
+        match self.plant_timestamp {
+            Some(planted) if !self.planted_in_future(timestamp, config) => planted.saturating_add(grow_time).saturating_sub(timestamp),
+            _ => grow_time,
+        }
+    }
+
+    /// Planted further in the future than an auto-replant waiting for the soil to rest could be
+    pub fn planted_in_future(&self, now: u128, config: &GameConfig) -> bool {
+        self.plant_timestamp.is_some_and(|planted| planted > now.saturating_add(config.rest_time(self.crop)))
     }
 
-    pub fn farm(&mut self) -> Result<()> {
+    pub fn update_elapsed_growth(&mut self, now: u128) {
+        self.elapsed_growth = self.age(now).unwrap_or(0);
+    }
+
+    pub fn farm(&mut self, now: u128, config: &GameConfig) -> Result<()> {
         if !self.planted() { return Err(GameError::AlreadyFarmed) }
-        if self.time_to_farm(util::timestamp()) > 0 { return Err(GameError::NotYetReady) }
+        if !self.is_ready(now, config) { return Err(GameError::NotYetReady) }
         self.plant_timestamp = None;
+        self.rest_until = Some(now + config.rest_time(self.crop));
+        self.harvest_count = self.harvest_count.saturating_add(1);
         Ok(())
     }
 
-    pub fn earnings(&self) -> Money {
-        let payout = self.crop.payout();
-        payout * (1. + self.crop.level_multiplier()).powi(self.level as i32)
+    /// Net profit from harvesting and replanting the field for `horizon_ms`, counting only whole cycles
+    pub fn expected_profit(&self, prices: Prices, horizon_ms: u128) -> Money {
+        let cycle = self.grow_time(prices.config) + prices.config.rest_time(self.crop);
+        if cycle == 0 { return Money::ZERO }
+        let cycles = horizon_ms / cycle;
+        (self.earnings(prices) - prices.planting_price(self.crop)) * cycles as f64
     }
-}
 
-#[derive(Clone, Serialize, Deserialize)]
-pub struct Farm {
-    pub name: String,
-    pub money: f64,
-    pub fields: Vec<Field>,
-}
+    pub fn greenhouse_price(&self, prices: Prices) -> Money {
+        Field::calculate_price(self.crop, prices) * GREENHOUSE_RATE
+    }
 
-impl Farm {
-    pub fn new(name: String) -> Self {
-        Self {
-            name,
-            money: 20.,
-            fields: Vec::new(),
-        }
+    pub fn insurance_price(&self, prices: Prices) -> Money {
+        self.earnings(prices) * INSURANCE_RATE
     }
 
-    pub fn available_crops() -> Vec<Crop> {
-        Crop::iter().collect::<Vec<Crop>>()
+    /// What the next harvest pays at the current level after soil depletion, before market events,
+    /// see `Farm::field_earnings`
+    fn earnings(&self, prices: Prices) -> Money {
+        self.earnings_at_level(prices, self.level) * self.soil_health()
     }
 
-    pub fn buy_field(&mut self, crop: Crop) -> Result<()> {
-        let price = crop.get_new_field_price();
-        if self.fields.len() >= 10 { return Err(GameError::MaxFieldsReached) }
-        if self.money < price { return Err(GameError::InsufficientFunds) }
-        self.fields.push(Field::new(crop));
-        self.money -= price;
-        Ok(())
+    /// Earnings multiplier from 1 down to `MIN_SOIL_HEALTH`, falling with every harvest
+    pub fn soil_health(&self) -> f64 {
+        (1. - SOIL_DEPLETION).powi(self.harvest_count.min(i32::MAX as u32) as i32).max(MIN_SOIL_HEALTH)
     }
 
-    pub fn level_up_field(&mut self, id: u32) -> Result<()> {
-        let field = match self.fields.get_mut(id as usize) {
-            Some(field) => field,
-            None => return Err(GameError::OutOfBounds),
-        };
+    /// Earnings once the field reaches its crop's base max level
+    fn max_earnings(&self, prices: Prices) -> Money {
+        self.earnings_at_level(prices, prices.config.max_level(self.crop))
+    }
+
+    /// Saturates at `Money::MAX` instead of overflowing for very high levels
+    fn earnings_at_level(&self, prices: Prices, level: Level) -> Money {
+        let multiplier = (1. + self.crop.level_multiplier()).powi(level as i32);
+        if !multiplier.is_finite() { return Money::MAX }
+        prices.payout(self.crop) * multiplier
+    }
+
+    /// Profit in dollars per second of growing, after paying for planting
+    fn income_rate_at_level(&self, prices: Prices, level: Level) -> f64 {
+        let grow_seconds = self.grow_time(prices.config) as f64 / 1000.;
+        (self.earnings_at_level(prices, level) - prices.planting_price(self.crop)).as_dollars() / grow_seconds
+    }
+}
 
-        if field.level_up_price()? > self.money { return Err(GameError::InsufficientFunds) }
-        let level_up_price = field.level_up_price()?;
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Investment {
+    BuyField(Crop),
+    LevelUpField(u32),
+}
 
-        field.level_up()?;
-        self.money -= level_up_price;
+#[derive(Debug, Clone, Copy)]
+pub struct Recommendation {
+    pub investment: Investment,
+    pub cost: Money,
+    /// Extra income in dollars per second the investment brings
+    pub income_gain: f64,
+}
 
-        Ok(())
+impl fmt::Display for Recommendation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.investment {
+            Investment::BuyField(crop) => write!(f, "Buy a {crop} field for {}", self.cost),
+            Investment::LevelUpField(id) => write!(f, "Level up field {} for {}", id + 1, self.cost),
+        }
     }
+}
 
-    pub fn plant_field(&mut self, id: u32) -> Result<()> {
-        let field = match self.fields.get_mut(id as usize) {
-            Some(field) => field,
-            None => return Err(GameError::OutOfBounds),
-        };
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationIssue {
+    NegativeMoney(Money),
+    TooManyFields { count: usize, max: usize },
+    RefundRateOutOfRange(f64),
+    LevelOutOfRange { field: usize, level: Level, max: Level },
+    PlantedInFuture { field: usize, timestamp: u128 },
+    /// The save named a crop this version doesn't know, the field was loaded as a placeholder crop
+    UnknownCrop { field: usize, crop: String, placeholder: Crop },
+    /// The save's research named a crop this version doesn't know, the entry was dropped
+    UnknownCropDropped { section: &'static str, crop: String },
+    /// A logged action named a crop this version doesn't know, it was loaded with a placeholder crop
+    UnknownCropInLog { action: usize, crop: String, placeholder: Crop },
+    /// The farm was played with a pricing strategy that hasn't been set again since loading
+    PricingStrategyMissing { saved: String, active: &'static str },
+}
 
-        if field.crop.get_planting_price() > self.money { return Err(GameError::InsufficientFunds) }
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationIssue::NegativeMoney(money) => write!(f, "Balance is negative ({money})"),
+            ValidationIssue::TooManyFields { count, max } => write!(f, "Farm has {count} fields, the maximum is {max}"),
+            ValidationIssue::RefundRateOutOfRange(rate) => write!(f, "Sell refund rate is {rate}, expected 0 to 1"),
+            ValidationIssue::LevelOutOfRange { field, level, max } => write!(f, "Field {} has level {level}, expected 1 to {max}", field + 1),
+            ValidationIssue::PlantedInFuture { field, timestamp } => write!(f, "Field {} was planted in the future ({timestamp})", field + 1),
+            ValidationIssue::UnknownCrop { field, crop, placeholder } => write!(f, "Field {} grows unknown crop \"{crop}\", loaded as {}", field + 1, placeholder.name()),
+            ValidationIssue::UnknownCropDropped { section, crop } => write!(f, "Dropped unknown crop \"{crop}\" from {section}"),
+            ValidationIssue::UnknownCropInLog { action, crop, placeholder } => write!(f, "Logged action {} names unknown crop \"{crop}\", loaded as {}", action + 1, placeholder.name()),
+            ValidationIssue::PricingStrategyMissing { saved, active } => write!(f, "Farm was played with pricing \"{saved}\", now using \"{active}\""),
+        }
+    }
+}
 
-        self.money -= field.crop.get_planting_price();
-        field.plant(util::timestamp())?;
+/// Something that happened on the farm that the player should know about
+#[derive(Debug, Clone, PartialEq)]
+pub enum GameEvent {
+    FieldReady { field: FieldId, crop: Crop },
+    DailyBonusAvailable,
+    /// A storm destroyed the field's planting, `payout` is what its insurance paid
+    CropLost { field: FieldId, crop: Crop, payout: Money },
+    MarketCrash { ends_at: u128 },
+    MarketRecovered,
+    /// Interest was paid on idle money, negative when the rate is a penalty
+    InterestPaid(Money),
+    /// Property tax was charged, selling `fields_sold` fields to cover it
+    TaxPaid { amount: Money, fields_sold: usize },
+    /// A logged player action, see `Action`
+    Action(Action),
+    HarvestedAll { fields: usize, payout: Money },
+    PlantedAll { fields: usize },
+    LeveledUpAll { fields: usize, cost: Money },
+}
 
-        Ok(())
+impl fmt::Display for GameEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameEvent::FieldReady { field, crop } => write!(f, "{crop} field {field} is ready to harvest"),
+            GameEvent::DailyBonusAvailable => write!(f, "Your daily bonus is available"),
+            GameEvent::CropLost { field, crop, payout } if *payout == Money::ZERO => write!(f, "A storm destroyed the {crop} on field {field}"),
+            GameEvent::CropLost { field, crop, payout } => write!(f, "A storm destroyed the {crop} on field {field}, insurance paid {payout}"),
+            GameEvent::MarketCrash { .. } => write!(f, "The market crashed, harvests pay half"),
+            GameEvent::MarketRecovered => write!(f, "The market has recovered"),
+            GameEvent::InterestPaid(amount) if amount.is_negative() => write!(f, "You paid {} for hoarding money", Money::ZERO - *amount),
+            GameEvent::InterestPaid(amount) => write!(f, "You earned {amount} in interest"),
+            GameEvent::TaxPaid { amount, fields_sold: 0 } => write!(f, "You paid {amount} in property tax"),
+            GameEvent::TaxPaid { amount, fields_sold } => write!(f, "You paid {amount} in property tax and had to sell {fields_sold} fields"),
+            GameEvent::Action(Action::Buy { field, crop, cost }) => write!(f, "Bought {crop} field {field} for {cost}"),
+            GameEvent::Action(Action::Plant { field, cost }) => write!(f, "Planted field {field} for {cost}"),
+            GameEvent::Action(Action::Harvest { field, payout }) => write!(f, "Harvested field {field} for {payout}"),
+            GameEvent::Action(Action::Sell { field, payout }) => write!(f, "Sold field {field} for {payout}"),
+            GameEvent::Action(Action::LevelUp { field, level, cost }) => write!(f, "Leveled field {field} up to {level} for {cost}"),
+            GameEvent::Action(Action::BuySeeds { crop, count, cost }) => write!(f, "Bought {count} {crop} seeds for {cost}"),
+            GameEvent::Action(Action::Greenhouse { field, cost }) => write!(f, "Built a greenhouse on field {field} for {cost}"),
+            GameEvent::Action(Action::Clear { field }) => write!(f, "Cleared field {field}"),
+            GameEvent::Action(Action::Rest { field }) => write!(f, "Left field {field} fallow"),
+            GameEvent::Action(Action::Convert { field, crop, cost }) => write!(f, "Converted field {field} to {crop} for {cost}"),
+            GameEvent::Action(Action::Research { crop, cost }) => write!(f, "Researched {crop} for {cost}"),
+            GameEvent::Action(Action::UnlockSkill { skill }) => write!(f, "Unlocked {skill}"),
+            GameEvent::Action(Action::Insure { field, cost }) => write!(f, "Insured field {field} for {cost}"),
+            GameEvent::Action(Action::DailyBonus { amount }) => write!(f, "Claimed a daily bonus of {amount}"),
+            GameEvent::Action(Action::Interest { amount }) => write!(f, "Interest changed your balance by {amount}"),
+            GameEvent::Action(Action::Tax { amount }) => write!(f, "You paid {amount} in property tax"),
+            GameEvent::Action(Action::Inflation { factor }) => write!(f, "Prices are now {:.0}% of what they were", factor * 100.),
+            GameEvent::Action(Action::CropLoss { field, payout }) => write!(f, "Lost the crop on field {field}, insurance paid {payout}"),
+            GameEvent::HarvestedAll { fields, payout } => write!(f, "Harvested {fields} fields for {payout}"),
+            GameEvent::PlantedAll { fields } => write!(f, "Planted {fields} fields"),
+            GameEvent::LeveledUpAll { fields, cost } => write!(f, "Leveled up {fields} fields for {cost}"),
+        }
     }
+}
 
-    pub fn farm_field(&mut self, id: u32) -> Result<Money> {
-        let field = match self.fields.get_mut(id as usize) {
-            Some(field) => field,
-            None => return Err(GameError::OutOfBounds),
-        };
+fn known_crop(crop: &serde_json::Value) -> bool {
+    Crop::iter().any(|known| serde_json::to_value(known).unwrap() == *crop)
+}
 
-        field.farm()?;
-        let payout = field.earnings();
-        self.money += payout;
-        Ok(payout)
+fn crop_label(crop: &serde_json::Value) -> String {
+    crop.as_str().map_or_else(|| crop.to_string(), str::to_string)
+}
+
+/// Replaces unknown crops in a serialized replay log with the placeholder crop
+fn sanitize_actions(actions: &mut serde_json::Value, issues: &mut Vec<ValidationIssue>) {
+    let Some(actions) = actions.as_array_mut() else { return };
+    for (i, action) in actions.iter_mut().enumerate() {
+        // Actions are saved as `{"timestamp": .., "action": {"Buy": {"crop": .., ..}}}`
+        let Some(variant) = action.get_mut("action").and_then(|action| action.as_object_mut()) else { continue };
+        for fields in variant.values_mut() {
+            let Some(crop) = fields.get_mut("crop") else { continue };
+            if known_crop(crop) { continue }
+            issues.push(ValidationIssue::UnknownCropInLog { action: i, crop: crop_label(crop), placeholder: UNKNOWN_CROP_PLACEHOLDER });
+            *crop = serde_json::to_value(UNKNOWN_CROP_PLACEHOLDER).unwrap();
+        }
     }
+}
 
-    pub fn sell_field(&mut self, id: u32) -> Result<Money> {
-        let field = match self.fields.get_mut(id as usize) {
-            Some(field) => field,
-            None => return Err(GameError::OutOfBounds),
-        };
+/// What a harvest did, see `Farm::harvest`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HarvestResult {
+    pub field: FieldId,
+    pub crop: Crop,
+    pub level: Level,
+    pub payout: Money,
+    /// The balance after the payout and any automatic replanting
+    pub balance: Money,
+    pub replanted: bool,
+}
 
-        let payout = field.crop.get_new_field_price() * 0.5 + field.crop.get_planting_price();
-        self.money += payout;
-        self.fields.remove(id as usize);
+/// What `Farm::level_up_all` did, fields are indices into `Farm::fields`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LevelUpSummary {
+    pub leveled: Vec<usize>,
+    pub maxed: Vec<usize>,
+    pub too_expensive: Vec<usize>,
+    pub cost: Money,
+}
 
-        Ok(payout)
+/// A temporary change to what harvests pay
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MarketEvent {
+    /// Harvest payouts are multiplied by this while the event lasts
+    pub multiplier: f64,
+    pub ends_at: u128,
+}
+
+/// A random offset within the configured fraction of the field's grow time, from the farm seed
+fn grow_jitter(config: &GameConfig, seed: u64, field: &Field, planted_at: u128) -> i64 {
+    let range = (field.base_grow_time(config) as f64 * config.grow_jitter()) as i64;
+    if range <= 0 { return 0 }
+    let roll = util::random(seed ^ field.id.0 ^ planted_at as u64) % (2 * range as u64 + 1);
+    roll as i64 - range
+}
+
+/// A player action, recorded in the replay log when `Farm::record_actions` is on
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Action {
+    Buy { field: FieldId, crop: Crop, cost: Money },
+    Plant { field: FieldId, cost: Money },
+    Harvest { field: FieldId, payout: Money },
+    Sell { field: FieldId, payout: Money },
+    LevelUp { field: FieldId, level: Level, cost: Money },
+    BuySeeds { crop: Crop, count: u32, cost: Money },
+    Greenhouse { field: FieldId, cost: Money },
+    Clear { field: FieldId },
+    Rest { field: FieldId },
+    Convert { field: FieldId, crop: Crop, cost: Money },
+    Research { crop: Crop, cost: Money },
+    UnlockSkill { skill: Skill },
+    Insure { field: FieldId, cost: Money },
+    DailyBonus { amount: Money },
+    /// Logged by `Farm::tick` without an event of its own, `InterestPaid` reports it
+    Interest { amount: Money },
+    /// Logged by `Farm::tick` without an event of its own, `TaxPaid` reports it
+    Tax { amount: Money },
+    /// Logged by `Farm::tick`, the inflation factor after prices rose
+    Inflation { factor: f64 },
+    /// Logged without an event of its own, `Farm::tick` reports storms as `CropLost`
+    CropLoss { field: FieldId, payout: Money },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TimestampedAction {
+    pub timestamp: u128,
+    pub action: Action,
+}
+
+/// The numbers the UI shows about a farm, see `Farm::summary`
+#[derive(Debug, Clone, PartialEq)]
+pub struct FarmSummary {
+    pub name: String,
+    pub money: Money,
+    pub field_count: usize,
+    pub ready_count: usize,
+    pub net_worth: Money,
+    pub earnings_rate: Money,
+}
+
+/// A field's state as plain data, for presentation layers to format however they like
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FieldView {
+    pub crop: Crop,
+    pub level: Level,
+    pub planted: bool,
+    /// Time left until harvest, `Some(0)` when ready and `None` when not planted
+    pub remaining_ms: Option<u128>,
+    pub earnings: Money,
+    pub sell_value: Money,
+    /// `None` at max level
+    pub level_up_price: Option<Money>,
+}
+
+/// A callback for `GameEvent`s, see `Farm::set_event_handler`. It isn't saved and clones
+/// of the farm don't get it, so snapshots and previews stay silent
+#[derive(Default)]
+pub struct EventHandler(Option<EventCallback>);
+
+pub type EventCallback = Box<dyn FnMut(&GameEvent) + Send>;
+
+impl Clone for EventHandler {
+    fn clone(&self) -> Self {
+        EventHandler(None)
     }
+}
 
-    pub fn save_to_path(&self, path: String) {
-        let json: String = serde_json::to_string(self).unwrap();
-        let file = std::fs::File::create(path).unwrap();
-        // write all to file
-        std::io::Write::write_all(&mut std::io::BufWriter::new(file), json.as_bytes()).unwrap();
+/// What viewers of a farm see, copied out so it can't change the farm, see `Farm::spectator_view`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpectatorView {
+    pub name: String,
+    pub money: Money,
+    pub season: Season,
+    pub market_crash: bool,
+    pub ready_count: usize,
+    pub next_ready_ms: Option<u128>,
+    pub fields: Vec<FieldView>,
+}
+
+/// A save holding either the full state, or a starting state and the actions taken since
+#[derive(Clone, Serialize, Deserialize)]
+pub enum SaveData {
+    Snapshot(Farm),
+    Replay { start: Farm, actions: Vec<TimestampedAction> },
+}
+
+/// The full state of a farm at some point, see `Farm::snapshot`
+#[derive(Clone)]
+pub struct FarmSnapshot(Farm);
+
+/// Snapshots from before the latest actions, newest last. Clones start empty so snapshots
+/// don't hold histories of their own
+#[derive(Default)]
+pub struct UndoHistory(VecDeque<FarmSnapshot>);
+
+impl Clone for UndoHistory {
+    fn clone(&self) -> Self {
+        UndoHistory::default()
     }
+}
 
-    pub fn load_from_path(path: String) -> Self {
-        let contents = std::fs::read_to_string(path).unwrap();
-        let farm: Farm = serde_json::from_str(&contents).unwrap();
-        farm
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Farm {
+    pub name: String,
+    pub money: Money,
+    pub fields: Vec<Field>,
+    /// Play time of all previous sessions, the current one is added on save
+    #[serde(default)]
+    pub total_play_time_ms: u128,
+    #[serde(skip, default = "util::timestamp")]
+    session_start: u128,
+    /// Seed for the farm's random events
+    #[serde(default)]
+    pub seed: u64,
+    #[serde(default)]
+    pub last_bonus_day: Option<u64>,
+    /// The last storm interval a storm was rolled for
+    #[serde(default)]
+    last_storm_roll: u128,
+    /// Fraction of the field price refunded when selling
+    #[serde(default = "default_sell_refund_rate")]
+    pub sell_refund_rate: f64,
+    #[serde(default)]
+    pub difficulty: Difficulty,
+    /// Game balance, not saved, a loaded farm uses the defaults until `set_config`
+    #[serde(skip)]
+    config: Arc<GameConfig>,
+    /// Prices and payouts, not saved, a loaded farm uses `StandardPricing` until `set_pricing`
+    #[serde(skip)]
+    pricing: Pricing,
+    /// Id of the pricing strategy the farm was played with, see `PricingStrategy::id`
+    #[serde(default = "default_pricing_strategy")]
+    pub pricing_strategy: String,
+    #[serde(default)]
+    next_field_id: u64,
+    #[serde(skip)]
+    bonus_reported_day: Option<u64>,
+    /// When the farm was last saved this session
+    #[serde(skip, default = "util::timestamp")]
+    pub last_autosave: u128,
+    /// Problems fixed up while loading, reported by `validate`
+    #[serde(skip)]
+    load_issues: Vec<ValidationIssue>,
+    #[serde(skip)]
+    event_handler: EventHandler,
+    #[serde(skip)]
+    undo_history: UndoHistory,
+    /// Set while replaying or simulating, actions are then not emitted or logged
+    #[serde(skip)]
+    simulating: bool,
+    /// A what-if copy from `sandbox` that is never saved
+    #[serde(skip)]
+    pub is_sandbox: bool,
+    /// Fraction of idle money added every interest interval, negative to penalize hoarding
+    #[serde(default)]
+    pub interest_rate: f64,
+    #[serde(default)]
+    pub last_interest: u128,
+    /// Fields only grow while the game is running, instead of by the wall clock
+    #[serde(default)]
+    pub pause_growth_offline: bool,
+    #[serde(default)]
+    pub last_saved: u128,
+    /// Ring the terminal bell when a field becomes ready
+    #[serde(default)]
+    pub notify_on_ready: bool,
+    /// Seeds owned per crop, planting a field uses one
+    #[serde(default)]
+    pub seeds: HashMap<Crop, u32>,
+    /// Fraction prices rise by every inflation interval, 0 for no inflation
+    #[serde(default)]
+    pub inflation_rate: f64,
+    /// What new field, planting and level up prices are multiplied by
+    #[serde(default = "default_multiplier")]
+    pub inflation_factor: f64,
+    #[serde(default)]
+    pub last_inflation: u128,
+    /// Fraction of net worth charged every tax interval, 0 for no tax
+    #[serde(default)]
+    pub tax_rate: f64,
+    #[serde(default)]
+    pub last_tax: u128,
+    #[serde(default)]
+    pub market_event: Option<MarketEvent>,
+    /// The last market interval a crash was rolled for
+    #[serde(default)]
+    last_market_roll: u128,
+    /// Earned by harvesting, spent on skills
+    #[serde(default)]
+    pub xp: u64,
+    #[serde(default)]
+    pub skills: Skills,
+    /// Extra max levels per crop bought with research
+    #[serde(default)]
+    pub max_level_bonus: HashMap<Crop, Level>,
+    /// Progress through the first-run tutorial, `None` once it is finished or skipped
+    #[serde(default)]
+    pub tutorial_step: Option<u8>,
+    #[serde(default)]
+    pub record_actions: bool,
+    /// Replay log of actions, only filled while `record_actions` is on
+    #[serde(default)]
+    pub actions: Vec<TimestampedAction>,
+}
+
+impl Farm {
+    pub fn new(name: String) -> Self {
+        Self::new_with_difficulty(name, Difficulty::Normal)
+    }
+
+    pub fn new_with_difficulty(name: String, difficulty: Difficulty) -> Self {
+        Self::new_with_config(name, difficulty, Arc::default())
+    }
+
+    pub fn new_with_config(name: String, difficulty: Difficulty, config: Arc<GameConfig>) -> Self {
+        Self {
+            name,
+            money: config.starting_money.unwrap_or(difficulty.starting_money()),
+            fields: Vec::new(),
+            total_play_time_ms: 0,
+            session_start: util::timestamp(),
+            seed: util::random(util::timestamp() as u64),
+            last_bonus_day: None,
+            last_storm_roll: 0,
+            sell_refund_rate: difficulty.sell_refund_rate(),
+            difficulty,
+            config,
+            pricing: Pricing::default(),
+            pricing_strategy: STANDARD_PRICING_ID.to_string(),
+            next_field_id: 0,
+            bonus_reported_day: None,
+            last_autosave: util::timestamp(),
+            load_issues: Vec::new(),
+            event_handler: EventHandler::default(),
+            undo_history: UndoHistory::default(),
+            simulating: false,
+            is_sandbox: false,
+            interest_rate: 0.,
+            last_interest: util::timestamp(),
+            pause_growth_offline: false,
+            last_saved: 0,
+            notify_on_ready: false,
+            seeds: HashMap::new(),
+            inflation_rate: 0.,
+            inflation_factor: 1.,
+            last_inflation: util::timestamp(),
+            tax_rate: 0.,
+            last_tax: util::timestamp(),
+            market_event: None,
+            last_market_roll: 0,
+            xp: 0,
+            skills: Skills::default(),
+            max_level_bonus: HashMap::new(),
+            tutorial_step: Some(0),
+            record_actions: false,
+            actions: Vec::new(),
+        }
+    }
+
+    /// Reports what changed since the last tick, each event is only reported once
+    pub fn tick(&mut self, now: u128) -> Vec<GameEvent> {
+        let mut events = Vec::new();
+        for field in self.fields.iter_mut() {
+            field.update_elapsed_growth(now);
+            if !field.is_ready(now, &self.config) || field.ready_reported { continue }
+            field.ready_reported = true;
+            events.push(GameEvent::FieldReady { field: field.id, crop: field.crop });
+        }
+
+        events.extend(self.roll_storm(now));
+
+        let today = util::day(now);
+        let bonus_claimed = self.last_bonus_day.is_some_and(|last| last >= today);
+        if !bonus_claimed && self.bonus_reported_day != Some(today) {
+            self.bonus_reported_day = Some(today);
+            events.push(GameEvent::DailyBonusAvailable);
+        }
+
+        events.extend(self.update_market(now));
+
+        let interest = self.apply_interest(now);
+        if interest != Money::ZERO {
+            events.push(GameEvent::InterestPaid(interest));
+        }
+
+        self.apply_inflation(now);
+
+        let fields_before = self.fields.len();
+        let tax = self.apply_tax(now);
+        if tax != Money::ZERO {
+            events.push(GameEvent::TaxPaid { amount: tax, fields_sold: fields_before - self.fields.len() });
+        }
+        for event in &events {
+            self.emit(event);
+        }
+        events
+    }
+
+    /// Ends an expired market event and rolls for a crash once per market interval
+    fn update_market(&mut self, now: u128) -> Option<GameEvent> {
+        if self.market_event.is_some_and(|event| now >= event.ends_at) {
+            self.market_event = None;
+            return Some(GameEvent::MarketRecovered)
+        }
+        let interval = now / MARKET_INTERVAL_MS;
+        if self.market_event.is_some() || interval == self.last_market_roll { return None }
+        self.last_market_roll = interval;
+
+        let chance = MARKET_CRASH_CHANCE * self.difficulty.market_volatility();
+        let roll = (util::random(self.seed ^ interval as u64) % 10_000) as f64 / 10_000.;
+        if roll >= chance { return None }
+        let ends_at = now + MARKET_INTERVAL_MS;
+        self.market_event = Some(MarketEvent { multiplier: 0.5, ends_at });
+        Some(GameEvent::MarketCrash { ends_at })
+    }
+
+    /// The market event currently affecting payouts
+    pub fn active_market_event(&self, now: u128) -> Option<MarketEvent> {
+        self.market_event.filter(|event| now < event.ends_at)
+    }
+
+    pub fn market_multiplier(&self, now: u128) -> f64 {
+        self.active_market_event(now).map_or(1., |event| event.multiplier)
+    }
+
+    pub fn current_season(&self, now: u128) -> Season {
+        Season::at(now)
+    }
+
+    /// What harvesting the field pays right now, including market events, seasons and diversity
+    pub fn field_earnings(&self, field: &Field, now: u128) -> Money {
+        field.earnings(self.prices()) * self.market_multiplier(now) * field.crop.season_multiplier(Season::at(now)) * self.diversity_bonus()
+    }
+
+    /// What harvesting the field pays once it reaches its crop's max level, before market events, seasons and diversity
+    pub fn max_earnings(&self, field: &Field) -> Money {
+        field.max_earnings(self.prices())
+    }
+
+    /// Like `Field::expected_profit`, with the farm's prices
+    pub fn expected_profit(&self, field: &Field, horizon_ms: u128) -> Money {
+        field.expected_profit(self.prices(), horizon_ms)
+    }
+
+    pub fn insurance_price(&self, field: &Field) -> Money {
+        field.insurance_price(self.prices())
+    }
+
+    pub fn greenhouse_price(&self, field: &Field) -> Money {
+        field.greenhouse_price(self.prices())
+    }
+
+    /// Earnings multiplier for owning at least one field of every crop, 1 otherwise
+    pub fn diversity_bonus(&self) -> f64 {
+        let all_crops = Crop::iter().all(|crop| self.fields.iter().any(|f| f.crop == crop));
+        if all_crops { 1. + DIVERSITY_BONUS } else { 1. }
+    }
+
+    /// Pays interest for every full interval since it was last paid, returns the change in money
+    pub fn apply_interest(&mut self, now: u128) -> Money {
+        if self.last_interest == 0 {
+            self.last_interest = now;
+            return Money::ZERO
+        }
+        let intervals = now.saturating_sub(self.last_interest) / INTEREST_INTERVAL_MS;
+        if intervals == 0 { return Money::ZERO }
+        self.last_interest += intervals * INTEREST_INTERVAL_MS;
+
+        let before = self.money;
+        let growth = (1. + self.interest_rate.max(-1.)).powi(intervals.min(i32::MAX as u128) as i32);
+        self.money = self.money * growth;
+        let amount = self.money - before;
+        if amount != Money::ZERO { self.log_action(Action::Interest { amount }, now) }
+        amount
+    }
+
+    /// Charges tax on net worth for every full interval since it was last charged, returns
+    /// the amount paid. Fields are sold cheapest first when the money doesn't cover it
+    pub fn apply_tax(&mut self, now: u128) -> Money {
+        if self.last_tax == 0 {
+            self.last_tax = now;
+            return Money::ZERO
+        }
+        let intervals = now.saturating_sub(self.last_tax) / TAX_INTERVAL_MS;
+        if intervals == 0 { return Money::ZERO }
+        self.last_tax += intervals * TAX_INTERVAL_MS;
+        if self.tax_rate <= 0. { return Money::ZERO }
+
+        let tax = self.net_worth() * (self.tax_rate.min(1.) * intervals as f64);
+        while self.money < tax {
+            let cheapest = self.fields.iter().enumerate()
+                .min_by_key(|(_, f)| self.sell_value(f))
+                .map(|(i, _)| i as u32);
+            match cheapest {
+                Some(id) => { let _ = self.sell_field(id); },
+                None => break,
+            }
+        }
+        let paid = tax.min(self.money);
+        self.money -= paid;
+        if paid != Money::ZERO { self.log_action(Action::Tax { amount: paid }, now) }
+        paid
+    }
+
+    /// Raises prices by the inflation rate for every full interval since they last rose
+    pub fn apply_inflation(&mut self, now: u128) {
+        if self.last_inflation == 0 {
+            self.last_inflation = now;
+            return
+        }
+        let intervals = now.saturating_sub(self.last_inflation) / INFLATION_INTERVAL_MS;
+        if intervals == 0 { return }
+        self.last_inflation += intervals * INFLATION_INTERVAL_MS;
+        let growth = (1. + self.inflation_rate.max(0.)).powi(intervals.min(i32::MAX as u128) as i32);
+        if growth == 1. { return }
+        self.inflation_factor *= growth;
+        self.log_action(Action::Inflation { factor: self.inflation_factor }, now);
+    }
+
+    pub fn set_inflation_rate(&mut self, rate: f64) -> Result<()> {
+        if !(0. ..=1.).contains(&rate) { return Err(GameError::InvalidRate) }
+        self.inflation_rate = rate;
+        Ok(())
+    }
+
+    /// The price of a new field of `crop` after inflation
+    pub fn field_price(&self, crop: Crop) -> Money {
+        self.prices().new_field_price(crop) * self.inflation_factor
+    }
+
+    /// The price of planting `crop` after inflation
+    pub fn planting_price(&self, crop: Crop) -> Money {
+        self.prices().planting_price(crop) * self.inflation_factor
+    }
+
+    /// Sets the balance directly, for tests and admin tools
+    pub fn set_money(&mut self, amount: Money) -> Result<()> {
+        if amount.is_negative() { return Err(GameError::NegativeAmount) }
+        self.money = amount;
+        Ok(())
+    }
+
+    pub fn set_tax_rate(&mut self, rate: f64) -> Result<()> {
+        if !(0. ..=1.).contains(&rate) { return Err(GameError::InvalidRate) }
+        self.tax_rate = rate;
+        Ok(())
+    }
+
+    /// With `pause_growth_offline`, moves plantings forward so they have grown only as long as
+    /// they had when saved, the time since `last_saved` doesn't count
+    pub fn resume_growth(&mut self, now: u128) {
+        if !self.pause_growth_offline || self.last_saved == 0 { return }
+        for field in self.fields.iter_mut() {
+            if field.planted() {
+                field.plant_timestamp = Some(now.saturating_sub(field.elapsed_growth));
+            }
+        }
+    }
+
+    pub fn toggle_notify_on_ready(&mut self) -> bool {
+        self.notify_on_ready = !self.notify_on_ready;
+        self.notify_on_ready
+    }
+
+    pub fn toggle_pause_growth_offline(&mut self) -> bool {
+        self.pause_growth_offline = !self.pause_growth_offline;
+        self.pause_growth_offline
+    }
+
+    /// Calls `handler` with every tick event and player action from now on, replacing any earlier handler
+    pub fn set_event_handler(&mut self, handler: EventCallback) {
+        self.event_handler = EventHandler(Some(handler));
+    }
+
+    pub fn clear_event_handler(&mut self) {
+        self.event_handler = EventHandler(None);
+    }
+
+    fn emit(&mut self, event: &GameEvent) {
+        if let Some(handler) = self.event_handler.0.as_mut() { handler(event) }
+    }
+
+    fn record(&mut self, action: Action, now: u128) {
+        self.advance_tutorial(&action);
+        if !self.simulating { self.emit(&GameEvent::Action(action)) }
+        self.log_action(action, now);
+    }
+
+    /// Adds the action to the replay log and diagnostic log without emitting it, for changes with events of their own
+    fn log_action(&mut self, action: Action, now: u128) {
+        if !self.simulating { logging::action(&action) }
+        if !self.record_actions { return }
+        self.actions.push(TimestampedAction { timestamp: now, action });
+    }
+
+    /// Moves the tutorial on if `action` is the one the current step asks for
+    pub fn advance_tutorial(&mut self, action: &Action) {
+        let Some(step) = self.tutorial_step else { return };
+        let expected = matches!((step, action), (0, Action::Buy { .. }) | (1, Action::Plant { .. }) | (2, Action::Harvest { .. }));
+        if !expected { return }
+        self.tutorial_step = if step + 1 < TUTORIAL_STEPS { Some(step + 1) } else { None };
+    }
+
+    /// What the tutorial asks the player to do next
+    pub fn tutorial_prompt(&self) -> Option<&'static str> {
+        match self.tutorial_step? {
+            0 => Some("Buy your first field from the shop"),
+            1 => Some("Buy seeds and plant your new field"),
+            2 => Some("Harvest the field once it's ready"),
+            _ => None,
+        }
+    }
+
+    pub fn skip_tutorial(&mut self) {
+        self.tutorial_step = None;
+    }
+
+    /// The replay log as JSON, oldest action first
+    pub fn export_replay(&self) -> String {
+        serde_json::to_string_pretty(&self.actions).unwrap()
+    }
+
+    /// The farm as a `SaveData` snapshot with only the latest `COMPACT_LOG_LENGTH` actions of its replay log
+    pub fn compact_save(&self) -> String {
+        serde_json::to_string(&self.compact_snapshot()).unwrap()
+    }
+
+    /// Like `compact_save`, as a value for apps that embed the save in their own JSON, see `from_json_value`
+    pub fn to_json_value(&self) -> serde_json::Value {
+        serde_json::to_value(self.compact_snapshot()).unwrap()
+    }
+
+    fn compact_snapshot(&self) -> SaveData {
+        let mut farm = self.clone();
+        let start = farm.actions.len().saturating_sub(COMPACT_LOG_LENGTH);
+        farm.actions.drain(..start);
+        SaveData::Snapshot(farm)
+    }
+
+    /// Rebuilds the farm that results from taking `actions` starting from this farm.
+    /// Settings, merges and market events aren't logged, logged actions carry their own prices and payouts
+    pub fn replay(&self, actions: &[TimestampedAction]) -> Result<Farm> {
+        let mut farm = self.clone();
+        farm.simulating = true;
+        for action in actions {
+            farm.apply(action)?;
+        }
+        farm.simulating = false;
+        Ok(farm)
+    }
+
+    fn apply(&mut self, action: &TimestampedAction) -> Result<()> {
+        let now = action.timestamp;
+        match action.action {
+            Action::Buy { field, crop, cost } => {
+                self.money = self.money.checked_sub(cost).ok_or(GameError::InsufficientFunds)?;
+                let mut new_field = self.new_field(crop);
+                new_field.id = field;
+                self.next_field_id = self.next_field_id.max(field.0);
+                self.fields.push(new_field);
+            },
+            Action::Plant { field, cost } => {
+                let index = self.index_of(field)? as usize;
+                // Logs from before seeds paid for planting directly
+                if cost == Money::ZERO { self.take_seed(self.fields[index].crop)? }
+                let money = self.money.checked_sub(cost).ok_or(GameError::InsufficientFunds)?;
+                let field = &mut self.fields[index];
+                field.plant_after_rest(now)?;
+                field.grow_jitter_ms = grow_jitter(&self.config, self.seed, field, now);
+                self.money = money;
+            },
+            Action::Harvest { field, payout } => {
+                let index = self.index_of(field)? as usize;
+                self.fields[index].farm(now, &self.config)?;
+                self.money += payout;
+                self.xp += XP_PER_HARVEST;
+            },
+            Action::Sell { field, payout } => {
+                let index = self.index_of(field)? as usize;
+                self.fields.remove(index);
+                self.money += payout;
+            },
+            Action::LevelUp { field, level, cost } => {
+                let index = self.index_of(field)? as usize;
+                self.money = self.money.checked_sub(cost).ok_or(GameError::InsufficientFunds)?;
+                self.fields[index].level = level;
+            },
+            Action::BuySeeds { crop, count, cost } => {
+                self.money = self.money.checked_sub(cost).ok_or(GameError::InsufficientFunds)?;
+                *self.seeds.entry(crop).or_insert(0) += count;
+            },
+            Action::Greenhouse { field, cost } => {
+                let index = self.index_of(field)? as usize;
+                self.money = self.money.checked_sub(cost).ok_or(GameError::InsufficientFunds)?;
+                self.fields[index].grow_speed_bonus = GREENHOUSE_BONUS;
+            },
+            Action::Clear { field } => {
+                let index = self.index_of(field)? as usize;
+                let field = &mut self.fields[index];
+                field.plant_timestamp = None;
+                field.grow_jitter_ms = 0;
+            },
+            Action::Rest { field } => {
+                let index = self.index_of(field)? as usize;
+                let field = &mut self.fields[index];
+                field.rest_until = Some(now + field.grow_time(&self.config));
+                field.harvest_count = 0;
+            },
+            Action::Convert { field, crop, cost } => {
+                let index = self.index_of(field)? as usize;
+                self.money = self.money.checked_sub(cost).ok_or(GameError::InsufficientFunds)?;
+                self.fields[index].crop = crop;
+                self.fields[index].level = 1;
+            },
+            Action::Research { crop, cost } => {
+                self.money = self.money.checked_sub(cost).ok_or(GameError::InsufficientFunds)?;
+                *self.max_level_bonus.entry(crop).or_insert(0) += 1;
+            },
+            Action::UnlockSkill { skill } => self.learn(skill)?,
+            Action::Insure { field, cost } => {
+                let index = self.index_of(field)? as usize;
+                self.money = self.money.checked_sub(cost).ok_or(GameError::InsufficientFunds)?;
+                self.fields[index].insured = true;
+            },
+            Action::DailyBonus { amount } => {
+                self.money += amount;
+                self.last_bonus_day = Some(util::day(now));
+            },
+            Action::Interest { amount } => self.money += amount,
+            Action::Tax { amount } => self.money -= amount,
+            Action::Inflation { factor } => self.inflation_factor = factor,
+            Action::CropLoss { field, payout } => {
+                let index = self.index_of(field)? as usize;
+                self.fields[index].plant_timestamp = None;
+                self.fields[index].insured = false;
+                self.money += payout;
+            },
+        }
+        self.record(action.action, now);
+        Ok(())
+    }
+
+    /// Loads a farm from either kind of save
+    pub fn rebuild(data: SaveData) -> Result<Farm> {
+        match data {
+            SaveData::Snapshot(farm) => Ok(farm),
+            SaveData::Replay { start, actions } => start.replay(&actions),
+        }
+    }
+
+    fn next_field_id(&mut self) -> FieldId {
+        self.next_field_id += 1;
+        FieldId(self.next_field_id)
+    }
+
+    /// Gives ids to fields from saves made before ids existed
+    fn assign_missing_ids(&mut self) {
+        self.next_field_id = self.fields.iter().map(|f| f.id.0).max().unwrap_or(0).max(self.next_field_id);
+        for i in 0..self.fields.len() {
+            if self.fields[i].id == FieldId::default() {
+                self.fields[i].id = self.next_field_id();
+            }
+        }
+    }
+
+    pub fn field(&self, id: FieldId) -> Option<&Field> {
+        self.fields.iter().find(|f| f.id == id)
+    }
+
+    pub fn field_mut(&mut self, id: FieldId) -> Option<&mut Field> {
+        self.fields.iter_mut().find(|f| f.id == id)
+    }
+
+    /// Current index of the field, for the index based methods
+    pub fn index_of(&self, id: FieldId) -> Result<u32> {
+        self.fields.iter().position(|f| f.id == id).map(|i| i as u32).ok_or(GameError::UnknownField)
+    }
+
+    pub fn field_id(&self, index: u32) -> Result<FieldId> {
+        self.fields.get(index as usize).map(|f| f.id).ok_or(GameError::OutOfBounds)
+    }
+
+    pub fn plant_field_by_id(&mut self, id: FieldId) -> Result<()> {
+        self.plant_field(self.index_of(id)?)
+    }
+
+    pub fn farm_field_by_id(&mut self, id: FieldId) -> Result<(Money, bool)> {
+        self.farm_field(self.index_of(id)?)
+    }
+
+    pub fn level_up_field_by_id(&mut self, id: FieldId) -> Result<()> {
+        self.level_up_field(self.index_of(id)?)
+    }
+
+    pub fn sell_field_by_id(&mut self, id: FieldId) -> Result<Money> {
+        self.sell_field(self.index_of(id)?)
+    }
+
+    pub fn config(&self) -> &GameConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: Arc<GameConfig>) {
+        self.config = config;
+    }
+
+    /// Replaces the prices and payouts the farm uses, see `PricingStrategy`
+    pub fn set_pricing(&mut self, strategy: impl PricingStrategy + 'static) {
+        self.pricing_strategy = strategy.id().to_string();
+        self.pricing = Pricing::new(strategy);
+    }
+
+    pub fn pricing(&self) -> &dyn PricingStrategy {
+        self.pricing.strategy()
+    }
+
+    /// The farm's pricing strategy with its config, for `Field`'s prices and earnings
+    pub fn prices(&self) -> Prices<'_> {
+        Prices::new(self.pricing(), &self.config)
+    }
+
+    /// Grants a random bonus once per day
+    pub fn claim_daily_bonus(&mut self, now: u128) -> Result<Money> {
+        let day = util::day(now);
+        if self.last_bonus_day.is_some_and(|last| last >= day) { return Err(GameError::BonusAlreadyClaimed) }
+        let range = DAILY_BONUS_MAX - DAILY_BONUS_MIN + 1;
+        let bonus = Money::dollars((DAILY_BONUS_MIN + util::random(self.seed ^ day) % range) as i64);
+        self.money += bonus;
+        self.last_bonus_day = Some(day);
+        self.record(Action::DailyBonus { amount: bonus }, now);
+        Ok(bonus)
+    }
+
+    pub fn snapshot(&self) -> FarmSnapshot {
+        FarmSnapshot(self.clone())
+    }
+
+    /// Goes back to the snapshot, keeping the undo history and event handler
+    pub fn restore(&mut self, snapshot: FarmSnapshot) {
+        let undo_history = std::mem::take(&mut self.undo_history);
+        let event_handler = std::mem::take(&mut self.event_handler);
+        *self = snapshot.0;
+        self.undo_history = undo_history;
+        self.event_handler = event_handler;
+    }
+
+    /// A copy of the farm to experiment on, it can't be saved until `commit_sandbox` is called
+    pub fn sandbox(&self) -> Farm {
+        let mut farm = self.clone();
+        farm.is_sandbox = true;
+        farm
+    }
+
+    /// Turns a sandbox into a regular farm, keeping the changes made in it
+    pub fn commit_sandbox(&mut self) {
+        self.is_sandbox = false;
+    }
+
+    /// Remembers a snapshot from before an action so it can be undone, only the last
+    /// `UNDO_HISTORY_LIMIT` are kept
+    pub fn push_undo(&mut self, snapshot: FarmSnapshot) {
+        if self.undo_history.0.len() >= UNDO_HISTORY_LIMIT { self.undo_history.0.pop_front(); }
+        self.undo_history.0.push_back(snapshot);
+    }
+
+    /// Reverts the most recent action passed to `push_undo`
+    pub fn undo(&mut self) -> Result<()> {
+        let snapshot = self.undo_history.0.pop_back().ok_or(GameError::NothingToUndo)?;
+        self.restore(snapshot);
+        Ok(())
+    }
+
+    pub fn undo_count(&self) -> usize {
+        self.undo_history.0.len()
+    }
+
+    /// Cleans up a farm name, stripping control characters and surrounding whitespace
+    pub fn validate_name(name: &str) -> Result<String> {
+        let name = util::strip_control(name).trim().to_string();
+        if name.is_empty() { return Err(GameError::EmptyName) }
+        if name.chars().count() > MAX_NAME_LENGTH { return Err(GameError::NameTooLong) }
+        Ok(name)
+    }
+
+    pub fn rename(&mut self, name: String) -> Result<()> {
+        self.name = Farm::validate_name(&name)?;
+        Ok(())
+    }
+
+    pub fn set_sell_refund_rate(&mut self, rate: f64) -> Result<()> {
+        if !(0. ..=1.).contains(&rate) { return Err(GameError::InvalidRate) }
+        self.sell_refund_rate = rate;
+        Ok(())
+    }
+
+    pub fn play_time(&self) -> Duration {
+        let session = util::timestamp().saturating_sub(self.session_start);
+        Duration::from_millis((self.total_play_time_ms + session) as u64)
+    }
+
+    fn record_play_time(&mut self, now: u128) {
+        self.total_play_time_ms += now.saturating_sub(self.session_start);
+        self.session_start = now;
+    }
+
+    pub fn available_crops() -> Vec<Crop> {
+        Crop::iter().collect::<Vec<Crop>>()
+    }
+
+    pub fn field_count(&self) -> usize {
+        self.fields.len()
+    }
+
+    /// Whether the farm has reached the field limit
+    pub fn is_full(&self) -> bool {
+        self.field_count() >= self.config.max_fields()
+    }
+
+    pub fn count_of(&self, crop: Crop) -> usize {
+        self.fields.iter().filter(|f| f.crop == crop).count()
+    }
+
+    /// The crops the shop sells today, Wheat is always sold and the rest depends on the seed
+    pub fn shop_rotation(&self, now: u128) -> Vec<Crop> {
+        let day = util::day(now);
+        Crop::iter().enumerate()
+            .filter(|(i, crop)| *crop == Crop::Wheat || util::random(self.seed ^ day ^ ((*i as u64) << 32)).is_multiple_of(2))
+            .map(|(_, crop)| crop)
+            .collect()
+    }
+
+    /// Whether the farm has a max level field of the crop's prerequisite, if it has one
+    pub fn prerequisite_met(&self, crop: Crop) -> bool {
+        crop.prerequisite().is_none_or(|required| {
+            self.fields.iter().any(|f| f.crop == required && f.level >= self.config.max_level(required))
+        })
+    }
+
+    pub fn owns_all_crops(&self) -> bool {
+        Farm::available_crops().into_iter().all(|crop| self.count_of(crop) > 0)
+    }
+
+    fn new_field(&self, crop: Crop) -> Field {
+        let mut field = Field::new(crop);
+        field.grow_time_multiplier = self.difficulty.grow_time_multiplier();
+        if self.skills.has(Skill::GreenThumb) { field.grow_time_multiplier *= GREEN_THUMB_MULTIPLIER }
+        field
+    }
+
+    /// Spends XP on a skill
+    pub fn unlock_skill(&mut self, skill: Skill) -> Result<()> {
+        if self.skills.has(skill) { return Err(GameError::SkillAlreadyUnlocked) }
+        self.learn(skill)?;
+        self.record(Action::UnlockSkill { skill }, util::timestamp());
+        Ok(())
+    }
+
+    fn learn(&mut self, skill: Skill) -> Result<()> {
+        self.xp = self.xp.checked_sub(skill.xp_cost()).ok_or(GameError::NotEnoughXp)?;
+        self.skills.0.insert(skill);
+        if skill == Skill::GreenThumb {
+            for field in self.fields.iter_mut() {
+                field.grow_time_multiplier *= GREEN_THUMB_MULTIPLIER;
+            }
+        }
+        Ok(())
+    }
+
+    /// The crop's max level including research
+    pub fn max_level(&self, crop: Crop) -> Level {
+        let bonus = self.max_level_bonus.get(&crop).copied().unwrap_or(0);
+        self.config.max_level(crop).saturating_add(bonus)
+    }
+
+    /// Price of researching one more max level for the crop, rising with every research
+    pub fn research_price(&self, crop: Crop) -> Money {
+        let researched = self.max_level_bonus.get(&crop).copied().unwrap_or(0);
+        self.prices().new_field_price(crop) * (RESEARCH_RATE * (researched as f64 + 1.))
+    }
+
+    /// Raises the crop's max level by one
+    pub fn research_max_level(&mut self, crop: Crop) -> Result<()> {
+        if self.max_level(crop) == Level::MAX { return Err(GameError::MaxLevelReached) }
+        let cost = self.research_price(crop);
+        self.money = self.money.checked_sub(cost).ok_or(GameError::InsufficientFunds)?;
+        *self.max_level_bonus.entry(crop).or_insert(0) += 1;
+        self.record(Action::Research { crop, cost }, util::timestamp());
+        Ok(())
+    }
+
+    /// The field's state as plain data, with the farm's prices, refund rate and market
+    pub fn describe_field(&self, field: &Field, now: u128) -> FieldView {
+        FieldView {
+            crop: field.crop,
+            level: field.level,
+            planted: field.planted(),
+            remaining_ms: field.planted().then(|| field.time_to_farm(now, &self.config)),
+            earnings: self.field_earnings(field, now),
+            sell_value: self.sell_value(field),
+            level_up_price: self.level_up_price(field).ok(),
+        }
+    }
+
+    /// The total price of leveling the field up to `target`, every level up priced like `level_up_price`
+    pub fn level_up_cost_to(&self, field: &Field, target: Level) -> Result<Money> {
+        if target <= field.level { return Err(GameError::InvalidLevel) }
+        if target > self.max_level(field.crop) { return Err(GameError::MaxLevelReached) }
+        Ok((field.level..target).map(|level| self.adjust_level_up_price(self.prices().level_up_price(field.crop, level))).sum())
+    }
+
+    /// The field's level up price after inflation and skill discounts
+    pub fn level_up_price(&self, field: &Field) -> Result<Money> {
+        Ok(self.adjust_level_up_price(field.level_up_price(self.prices(), self.max_level(field.crop))?))
+    }
+
+    fn adjust_level_up_price(&self, price: Money) -> Money {
+        let price = price * self.inflation_factor;
+        if self.skills.has(Skill::CheapUpgrades) { return price * CHEAP_UPGRADES_MULTIPLIER }
+        price
+    }
+
+    pub fn buy_field(&mut self, crop: Crop, now: u128) -> Result<()> {
+        self.buy_fields(crop, 1, now).map(|_| ())
+    }
+
+    /// The cost of buying `count` fields of `crop`, or why they can't be bought, without buying them
+    pub fn preview_buy_fields(&self, crop: Crop, count: usize, now: u128) -> Result<Money> {
+        if !self.prerequisite_met(crop) { return Err(GameError::PrerequisiteNotMet) }
+        if !self.shop_rotation(now).contains(&crop) { return Err(GameError::NotInRotation) }
+        let price = self.field_price(crop) * count as f64;
+        if self.fields.len() + count > self.config.max_fields() { return Err(GameError::MaxFieldsReached) }
+        self.money.checked_sub(price).ok_or(GameError::InsufficientFunds)?;
+        Ok(price)
+    }
+
+    pub fn preview_buy(&self, crop: Crop, now: u128) -> Result<Money> {
+        self.preview_buy_fields(crop, 1, now)
+    }
+
+    /// The cost of leveling up the field, or why it can't be, without leveling it up
+    pub fn preview_level_up(&self, id: u32) -> Result<Money> {
+        let field = match self.fields.get(id as usize) {
+            Some(field) => field,
+            None => return Err(GameError::OutOfBounds),
+        };
+
+        let price = self.level_up_price(field)?;
+        self.money.checked_sub(price).ok_or(GameError::InsufficientFunds)?;
+        Ok(price)
+    }
+
+    /// Why the field can't be planted, if it can't, without planting it
+    pub fn preview_plant(&self, id: u32) -> Result<()> {
+        let field = match self.fields.get(id as usize) {
+            Some(field) => field,
+            None => return Err(GameError::OutOfBounds),
+        };
+
+        if field.planted() { return Err(GameError::AlreadyPlanted) }
+        if field.resting(util::timestamp()) { return Err(GameError::SoilResting) }
+        if self.seed_count(field.crop) == 0 { return Err(GameError::NoSeeds) }
+        Ok(())
+    }
+
+    pub fn seed_count(&self, crop: Crop) -> u32 {
+        self.seeds.get(&crop).copied().unwrap_or(0)
+    }
+
+    /// Buys `count` seeds of `crop` at its planting price, returns what they cost
+    pub fn buy_seeds(&mut self, crop: Crop, count: u32) -> Result<Money> {
+        let cost = self.planting_price(crop) * count as f64;
+        self.money = self.money.checked_sub(cost).ok_or(GameError::InsufficientFunds)?;
+        *self.seeds.entry(crop).or_insert(0) += count;
+        self.record(Action::BuySeeds { crop, count, cost }, util::timestamp());
+        Ok(cost)
+    }
+
+    fn take_seed(&mut self, crop: Crop) -> Result<()> {
+        let seeds = self.seeds.get_mut(&crop).filter(|seeds| **seeds > 0).ok_or(GameError::NoSeeds)?;
+        *seeds -= 1;
+        Ok(())
+    }
+
+    /// What selling the field would pay, without selling it
+    pub fn preview_sell(&self, id: u32) -> Result<Money> {
+        match self.fields.get(id as usize) {
+            Some(field) => Ok(self.sell_value(field)),
+            None => Err(GameError::OutOfBounds),
+        }
+    }
+
+    /// Buys `count` fields of `crop` and returns their indices, buys none if not all of them can be bought
+    pub fn buy_fields(&mut self, crop: Crop, count: usize, now: u128) -> Result<Vec<u32>> {
+        let price = self.preview_buy_fields(crop, count, now)?;
+        let mut indices = Vec::with_capacity(count);
+        for _ in 0..count {
+            let mut field = self.new_field(crop);
+            field.id = self.next_field_id();
+            indices.push(self.fields.len() as u32);
+            self.fields.push(field);
+        }
+        self.money -= price;
+        let cost = self.field_price(crop);
+        for &index in &indices {
+            let field = self.fields[index as usize].id;
+            self.record(Action::Buy { field, crop, cost }, now);
+        }
+        Ok(indices)
+    }
+
+    pub fn level_up_field(&mut self, id: u32) -> Result<()> {
+        let price = self.preview_level_up(id)?;
+        let max_level = self.max_level(self.fields[id as usize].crop);
+        let field = &mut self.fields[id as usize];
+
+        field.level_up(max_level)?;
+        self.money -= price;
+        let action = Action::LevelUp { field: field.id, level: field.level, cost: price };
+        self.record(action, util::timestamp());
+
+        Ok(())
+    }
+
+    /// Levels every field up by one, cheapest first so the money goes as far as possible
+    pub fn level_up_all(&mut self) -> LevelUpSummary {
+        let mut summary = LevelUpSummary::default();
+        let mut prices = Vec::new();
+        for (i, field) in self.fields.iter().enumerate() {
+            match self.level_up_price(field) {
+                Ok(price) => prices.push((price, i)),
+                Err(_) => summary.maxed.push(i),
+            }
+        }
+        prices.sort();
+
+        for (price, i) in prices {
+            match self.level_up_field(i as u32) {
+                Ok(()) => {
+                    summary.leveled.push(i);
+                    summary.cost += price;
+                }
+                Err(_) => summary.too_expensive.push(i),
+            }
+        }
+        summary
+    }
+
+    /// Plants the field, using up one of its crop's seeds
+    pub fn plant_field(&mut self, id: u32) -> Result<()> {
+        self.preview_plant(id)?;
+        let field = &mut self.fields[id as usize];
+
+        // Plant before taking the seed so a failed planting costs nothing
+        let now = util::timestamp();
+        field.plant(now)?;
+        field.grow_jitter_ms = grow_jitter(&self.config, self.seed, field, now);
+        let (field, crop) = (field.id, field.crop);
+        self.take_seed(crop)?;
+        self.record(Action::Plant { field, cost: Money::ZERO }, now);
+
+        Ok(())
+    }
+
+    /// Leaves the empty field fallow for a full grow time, restoring its soil health
+    pub fn rest_field(&mut self, id: u32) -> Result<()> {
+        let field = match self.fields.get_mut(id as usize) {
+            Some(field) => field,
+            None => return Err(GameError::OutOfBounds),
+        };
+
+        if field.planted() { return Err(GameError::AlreadyPlanted) }
+        let now = util::timestamp();
+        field.rest_until = Some(now + field.grow_time(&self.config));
+        field.harvest_count = 0;
+        let field = field.id;
+        self.record(Action::Rest { field }, now);
+        Ok(())
+    }
+
+    /// Abandons the crop growing on the field, without payout or refund
+    pub fn clear_field(&mut self, id: u32) -> Result<()> {
+        let field = match self.fields.get_mut(id as usize) {
+            Some(field) => field,
+            None => return Err(GameError::OutOfBounds),
+        };
+
+        if !field.planted() { return Err(GameError::NotPlanted) }
+        field.plant_timestamp = None;
+        field.grow_jitter_ms = 0;
+        let field = field.id;
+        self.record(Action::Clear { field }, util::timestamp());
+        Ok(())
+    }
+
+    /// Returns the payout and whether the field was automatically replanted
+    pub fn farm_field(&mut self, id: u32) -> Result<(Money, bool)> {
+        let result = self.harvest(id, util::timestamp())?;
+        Ok((result.payout, result.replanted))
+    }
+
+    /// Harvests the field, paying out its earnings and replanting it if it is flagged for it
+    pub fn harvest(&mut self, id: u32, now: u128) -> Result<HarvestResult> {
+        let field = match self.fields.get(id as usize) {
+            Some(field) => field,
+            None => return Err(GameError::OutOfBounds),
+        };
+
+        // Pay with the soil health from before this harvest depletes it
+        let payout = self.field_earnings(field, now);
+        self.fields[id as usize].farm(now, &self.config)?;
+        let field = &self.fields[id as usize];
+        self.xp += XP_PER_HARVEST;
+        let (field, crop, level) = (field.id, field.crop, field.level);
+        self.money += payout;
+        self.record(Action::Harvest { field, payout }, now);
+        let replanted = self.auto_replant(id as usize, now);
+        Ok(HarvestResult { field, crop, level, payout, balance: self.money, replanted })
+    }
+
+    /// Estimates the balance `horizon_ms` after `now` by harvesting every field as it becomes ready
+    /// on a copy of the farm, and with `auto_replant` replanting it once the soil has rested.
+    /// Replanting uses seeds while there are any, then pays the planting price
+    pub fn projected_balance(&self, now: u128, horizon_ms: u128, auto_replant: bool) -> Money {
+        let end = now.saturating_add(horizon_ms);
+        let mut farm = self.clone();
+        farm.record_actions = false;
+        farm.simulating = true;
+        for field in farm.fields.iter_mut() {
+            field.auto_replant = false;
+        }
+
+        loop {
+            let next = farm.fields.iter().enumerate()
+                .filter_map(|(i, f)| Some((i, f.plant_timestamp?.saturating_add(f.grow_time(&farm.config)))))
+                .min_by_key(|(_, ready_at)| *ready_at);
+            let Some((i, ready_at)) = next.filter(|(_, ready_at)| *ready_at <= end) else { break };
+            let at = ready_at.max(now);
+            if farm.harvest(i as u32, at).is_err() { break }
+
+            let crop = farm.fields[i].crop;
+            // A crop that takes no time would be replanted forever
+            if !auto_replant || farm.fields[i].base_grow_time(&farm.config) == 0 { continue }
+            if farm.take_seed(crop).is_err() {
+                let Some(money) = farm.money.checked_sub(farm.planting_price(crop)) else { continue };
+                farm.money = money;
+            }
+            let field = &mut farm.fields[i];
+            if field.plant_after_rest(at).is_ok() {
+                field.grow_jitter_ms = grow_jitter(&farm.config, farm.seed, field, at);
+            }
+        }
+        farm.money
+    }
+
+    /// Harvests every ready field, returns how many were harvested and the total payout
+    pub fn harvest_all(&mut self, now: u128) -> (usize, Money) {
+        let mut harvested = (0, Money::ZERO);
+        for i in 0..self.fields.len() {
+            let Ok(result) = self.harvest(i as u32, now) else { continue };
+            harvested.0 += 1;
+            harvested.1 += result.payout;
+        }
+        harvested
+    }
+
+    /// Plants every field that can be planted, returns how many were
+    pub fn plant_all(&mut self) -> usize {
+        (0..self.fields.len()).filter(|i| self.plant_field(*i as u32).is_ok()).count()
+    }
+
+    /// Harvests every ready field of `crop`, returns the total payout
+    pub fn harvest_crop(&mut self, crop: Crop, now: u128) -> Money {
+        let mut total = Money::ZERO;
+        for i in 0..self.fields.len() {
+            let field = &self.fields[i];
+            if field.crop != crop { continue }
+            let payout = self.field_earnings(field, now);
+            if self.fields[i].farm(now, &self.config).is_err() { continue }
+            total += payout;
+            self.money += payout;
+            self.xp += XP_PER_HARVEST;
+            let action = Action::Harvest { field: self.fields[i].id, payout };
+            self.record(action, now);
+            self.auto_replant(i, now);
+        }
+        total
+    }
+
+    /// Replants a harvested field if it is flagged for it and there's a seed for it
+    fn auto_replant(&mut self, index: usize, now: u128) -> bool {
+        let crop = self.fields[index].crop;
+        if !self.fields[index].auto_replant || self.seed_count(crop) == 0 { return false }
+        let field = &mut self.fields[index];
+        if field.plant_after_rest(now).is_err() { return false }
+        field.grow_jitter_ms = grow_jitter(&self.config, self.seed, field, now);
+        let field = field.id;
+        let _ = self.take_seed(crop);
+        self.record(Action::Plant { field, cost: Money::ZERO }, now);
+        true
+    }
+
+    /// Flips whether the field is replanted after harvesting, returns the new setting
+    pub fn toggle_auto_replant(&mut self, id: u32) -> Result<bool> {
+        let field = match self.fields.get_mut(id as usize) {
+            Some(field) => field,
+            None => return Err(GameError::OutOfBounds),
+        };
+
+        field.auto_replant = !field.auto_replant;
+        Ok(field.auto_replant)
+    }
+
+    pub fn ready_count(&self, now: u128) -> usize {
+        self.fields.iter().filter(|f| f.is_ready(now, &self.config)).count()
+    }
+
+    /// Field indices grouped by crop, crops without fields are left out
+    pub fn fields_by_crop(&self) -> HashMap<Crop, Vec<usize>> {
+        let mut groups: HashMap<Crop, Vec<usize>> = HashMap::new();
+        for (i, field) in self.fields.iter().enumerate() {
+            groups.entry(field.crop).or_default().push(i);
+        }
+        groups
+    }
+
+    /// Ready to harvest, or empty, done resting and with a seed owned or affordable
+    pub fn field_needs_attention(&self, field: &Field, now: u128) -> bool {
+        if field.needs_attention(now, &self.config) { return true }
+        let can_get_seed = self.seed_count(field.crop) > 0 || self.money >= self.planting_price(field.crop);
+        !field.planted() && !field.resting(now) && can_get_seed
+    }
+
+    /// Indices of the fields that need the player, see `field_needs_attention`
+    pub fn attention_list(&self, now: u128) -> Vec<usize> {
+        self.fields.iter().enumerate()
+            .filter(|(_, f)| self.field_needs_attention(f, now))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// The farm's display state at `now`, without the save data, undo history or handlers
+    pub fn spectator_view(&self, now: u128) -> SpectatorView {
+        SpectatorView {
+            name: self.name.clone(),
+            money: self.money,
+            season: Season::at(now),
+            market_crash: self.active_market_event(now).is_some(),
+            ready_count: self.ready_count(now),
+            next_ready_ms: self.time_until_next_ready(now),
+            fields: self.fields.iter().map(|field| self.describe_field(field, now)).collect(),
+        }
+    }
+
+    /// The shortest remaining grow time of any planted field, 0 if one is ready
+    pub fn time_until_next_ready(&self, now: u128) -> Option<u128> {
+        self.fields.iter().filter(|f| f.planted()).map(|f| f.time_to_farm(now, &self.config)).min()
+    }
+
+    pub fn summary(&self) -> FarmSummary {
+        FarmSummary {
+            name: self.name.clone(),
+            money: self.money,
+            field_count: self.field_count(),
+            ready_count: self.ready_count(util::timestamp()),
+            net_worth: self.net_worth(),
+            earnings_rate: self.earnings_rate_per_second(),
+        }
+    }
+
+    /// A one-line summary for status bars, colored unless colors are turned off
+    pub fn status_line(&self, now: u128) -> String {
+        format!("Farm: {} | {} | Fields {} ({} ready) | {}/s",
+            self.name,
+            self.money.to_string().bright_green(),
+            self.fields.len(),
+            self.ready_count(now),
+            self.earnings_rate_per_second().to_string().bright_green(),
+        )
+    }
+
+    /// Money plus what every field would sell for
+    pub fn net_worth(&self) -> Money {
+        self.money + self.fields.iter().map(|f| self.sell_value(f)).sum()
+    }
+
+    pub fn can_prestige(&self) -> bool {
+        self.net_worth() >= PRESTIGE_THRESHOLD
+    }
+
+    /// The earnings multiplier a prestige would give right now, `None` below the threshold
+    pub fn prestige_preview(&self) -> Option<f64> {
+        if !self.can_prestige() { return None }
+        let ratio = self.net_worth().as_dollars() / PRESTIGE_THRESHOLD.as_dollars();
+        Some(1. + ratio.sqrt() / 10.)
+    }
+
+    pub fn sell_value(&self, field: &Field) -> Money {
+        field.sell_value(self.prices(), self.sell_refund_rate)
+    }
+
+    pub fn sell_field(&mut self, id: u32) -> Result<Money> {
+        let payout = self.preview_sell(id)?;
+        let action = Action::Sell { field: self.fields[id as usize].id, payout };
+        self.money += payout;
+        self.fields.remove(id as usize);
+        self.record(action, util::timestamp());
+
+        Ok(payout)
+    }
+
+    /// Adds the other farm's fields and money to this one. The fields get new ids, everything
+    /// else (name, difficulty, bonus, play time) is kept from this farm. Nothing is merged if
+    /// the combined fields would exceed the field limit
+    pub fn merge(&mut self, other: &Farm) -> Result<()> {
+        if self.fields.len() + other.fields.len() > self.config.max_fields() { return Err(GameError::MaxFieldsReached) }
+        for field in &other.fields {
+            let mut field = field.clone();
+            field.id = self.next_field_id();
+            self.fields.push(field);
+        }
+        self.money += other.money;
+        Ok(())
+    }
+
+    /// Income per second from the fields currently growing
+    pub fn earnings_rate_per_second(&self) -> Money {
+        let now = util::timestamp();
+        let rate = self.fields.iter()
+            .filter(|f| f.planted() && f.grow_time(&self.config) > 0)
+            .map(|f| self.field_earnings(f, now).as_dollars() / (f.grow_time(&self.config) as f64 / 1000.))
+            .sum();
+        Money::from_dollars(rate)
+    }
+
+    /// Suggests the affordable purchase with the best income gain per dollar spent
+    pub fn best_investment(&self, now: u128) -> Option<Recommendation> {
+        let mut options = Vec::new();
+        if !self.is_full() {
+            for crop in self.shop_rotation(now).into_iter().filter(|c| self.prerequisite_met(*c)) {
+                let field = self.new_field(crop);
+                options.push(Recommendation {
+                    investment: Investment::BuyField(crop),
+                    cost: self.field_price(crop),
+                    income_gain: field.income_rate_at_level(self.prices(), field.level),
+                });
+            }
+        }
+        for (i, field) in self.fields.iter().enumerate() {
+            let Ok(cost) = self.level_up_price(field) else { continue };
+            options.push(Recommendation {
+                investment: Investment::LevelUpField(i as u32),
+                cost,
+                income_gain: field.income_rate_at_level(self.prices(), field.level + 1) - field.income_rate_at_level(self.prices(), field.level),
+            });
+        }
+
+        options.into_iter()
+            .filter(|r| r.cost <= self.money && r.income_gain > 0.)
+            .max_by(|a, b| (a.income_gain / a.cost.as_dollars()).total_cmp(&(b.income_gain / b.cost.as_dollars())))
+    }
+
+    pub fn export_csv(&self) -> String {
+        let now = util::timestamp();
+        let mut csv = String::from("index,crop,level,planted,time_remaining,earnings,sell_value\n");
+        for (i, field) in self.fields.iter().enumerate() {
+            let time_remaining = if field.planted() { field.time_to_farm(now, &self.config).to_string() } else { String::new() };
+            csv.push_str(&format!(
+                "{},{},{},{},{},{:.2},{:.2}\n",
+                i + 1,
+                util::csv_escape(field.crop.name()),
+                field.level,
+                field.planted(),
+                time_remaining,
+                self.field_earnings(field, now).as_dollars(),
+                self.sell_value(field).as_dollars(),
+            ));
+        }
+        csv
+    }
+
+    /// Checks the invariants the game relies on, reporting every broken one.
+    /// Useful after loading a save that may have been edited by hand.
+    pub fn validate(&self) -> std::result::Result<(), Vec<ValidationIssue>> {
+        let mut issues = self.load_issues.clone();
+        if self.money.is_negative() {
+            issues.push(ValidationIssue::NegativeMoney(self.money));
+        }
+        let max_fields = self.config.max_fields();
+        if self.fields.len() > max_fields {
+            issues.push(ValidationIssue::TooManyFields { count: self.fields.len(), max: max_fields });
+        }
+        if !(0. ..=1.).contains(&self.sell_refund_rate) {
+            issues.push(ValidationIssue::RefundRateOutOfRange(self.sell_refund_rate));
+        }
+        if self.pricing_strategy != self.pricing().id() {
+            issues.push(ValidationIssue::PricingStrategyMissing { saved: self.pricing_strategy.clone(), active: self.pricing().id() });
+        }
+
+        let now = util::timestamp();
+        for (i, field) in self.fields.iter().enumerate() {
+            let max = self.max_level(field.crop);
+            if field.level == 0 || field.level > max {
+                issues.push(ValidationIssue::LevelOutOfRange { field: i, level: field.level, max });
+            }
+            if let Some(timestamp) = field.plant_timestamp.filter(|_| field.planted_in_future(now, &self.config)) {
+                issues.push(ValidationIssue::PlantedInFuture { field: i, timestamp });
+            }
+        }
+
+        if issues.is_empty() { Ok(()) } else { Err(issues) }
+    }
+
+    pub fn buy_insurance(&mut self, id: u32) -> Result<()> {
+        let field = match self.fields.get(id as usize) {
+            Some(field) => field,
+            None => return Err(GameError::OutOfBounds),
+        };
+
+        if field.insured { return Err(GameError::AlreadyInsured) }
+        let cost = self.insurance_price(field);
+        self.money = self.money.checked_sub(cost).ok_or(GameError::InsufficientFunds)?;
+        let field = &mut self.fields[id as usize];
+        field.insured = true;
+        let field = field.id;
+        self.record(Action::Insure { field, cost }, util::timestamp());
+        Ok(())
+    }
+
+    /// Builds a greenhouse on the field, permanently cutting its grow time by `GREENHOUSE_BONUS`
+    pub fn buy_greenhouse(&mut self, id: u32) -> Result<()> {
+        let field = match self.fields.get(id as usize) {
+            Some(field) => field,
+            None => return Err(GameError::OutOfBounds),
+        };
+
+        if field.grow_speed_bonus > 0. { return Err(GameError::AlreadyUpgraded) }
+        let cost = self.greenhouse_price(field);
+        self.money = self.money.checked_sub(cost).ok_or(GameError::InsufficientFunds)?;
+        let field = &mut self.fields[id as usize];
+        field.grow_speed_bonus = GREENHOUSE_BONUS;
+        let field = field.id;
+        self.record(Action::Greenhouse { field, cost }, util::timestamp());
+        Ok(())
+    }
+
+    /// Destroys the crop growing on a field, e.g. from a storm or pests.
+    /// An insured field pays out its earnings instead and loses its insurance.
+    pub fn crop_loss(&mut self, id: u32) -> Result<Money> {
+        let field = match self.fields.get(id as usize) {
+            Some(field) => field,
+            None => return Err(GameError::OutOfBounds),
+        };
+
+        if !field.planted() { return Err(GameError::NotPlanted) }
+        let payout = if field.insured { field.earnings(self.prices()) } else { Money::ZERO };
+        let field = &mut self.fields[id as usize];
+        field.plant_timestamp = None;
+        field.insured = false;
+        self.money += payout;
+        let field = field.id;
+        self.log_action(Action::CropLoss { field, payout }, util::timestamp());
+        Ok(payout)
+    }
+
+    /// Moves every timestamp the farm keeps `ms` into the past, see `testkit::advance`.
+    /// Interval counters and days move back by whole intervals and days
+    #[cfg(any(test, feature = "testkit"))]
+    pub(crate) fn shift_timestamps(&mut self, ms: u128) {
+        // 0 means "never" for these, so they stay at least 1
+        for timestamp in [&mut self.last_interest, &mut self.last_tax, &mut self.last_inflation, &mut self.last_saved] {
+            if *timestamp != 0 { *timestamp = timestamp.saturating_sub(ms).max(1) }
+        }
+        self.last_autosave = self.last_autosave.saturating_sub(ms);
+        self.last_market_roll = self.last_market_roll.saturating_sub(ms / MARKET_INTERVAL_MS);
+        self.last_storm_roll = self.last_storm_roll.saturating_sub(ms / STORM_INTERVAL_MS);
+        if let Some(event) = self.market_event.as_mut() {
+            event.ends_at = event.ends_at.saturating_sub(ms);
+        }
+        let days = (ms / util::DAY_MILLIS) as u64;
+        for day in [&mut self.last_bonus_day, &mut self.bonus_reported_day].into_iter().flatten() {
+            *day = day.saturating_sub(days);
+        }
+        for field in self.fields.iter_mut() {
+            for timestamp in [&mut field.plant_timestamp, &mut field.rest_until].into_iter().flatten() {
+                *timestamp = timestamp.saturating_sub(ms);
+            }
+        }
+    }
+
+    /// Rolls once per storm interval for a storm, which destroys a random planted field's crop
+    fn roll_storm(&mut self, now: u128) -> Option<GameEvent> {
+        let interval = now / STORM_INTERVAL_MS;
+        if interval == self.last_storm_roll { return None }
+        self.last_storm_roll = interval;
+
+        let random = util::random(self.seed.rotate_left(17) ^ interval as u64);
+        if (random % 10_000) as f64 / 10_000. >= STORM_CHANCE { return None }
+        let planted = self.fields.iter().enumerate().filter(|(_, f)| f.planted()).map(|(i, _)| i).collect::<Vec<usize>>();
+        if planted.is_empty() { return None }
+        let index = planted[(random / 10_000) as usize % planted.len()];
+        let (field, crop) = (self.fields[index].id, self.fields[index].crop);
+        let payout = self.crop_loss(index as u32).ok()?;
+        Some(GameEvent::CropLost { field, crop, payout })
+    }
+
+    pub fn conversion_price(&self, crop: Crop) -> Money {
+        self.prices().new_field_price(crop) * CONVERSION_RATE
+    }
+
+    /// Switches an empty field to another crop, resetting its level, returns the price paid
+    pub fn convert_field(&mut self, id: u32, crop: Crop) -> Result<Money> {
+        if !self.prerequisite_met(crop) { return Err(GameError::PrerequisiteNotMet) }
+        let price = self.conversion_price(crop);
+        let field = match self.fields.get_mut(id as usize) {
+            Some(field) => field,
+            None => return Err(GameError::OutOfBounds),
+        };
+
+        if field.planted() { return Err(GameError::AlreadyPlanted) }
+        if field.crop == crop { return Err(GameError::SameCrop) }
+        self.money = self.money.checked_sub(price).ok_or(GameError::InsufficientFunds)?;
+        field.crop = crop;
+        field.level = 1;
+        let field = field.id;
+        self.record(Action::Convert { field, crop, cost: price }, util::timestamp());
+        Ok(price)
+    }
+
+    /// The fields with their indices in the given order, fields that compare equal keep their order
+    pub fn fields_sorted(&self, sort: FieldSort, now: u128) -> Vec<(usize, &Field)> {
+        let mut fields = self.fields.iter().enumerate().collect::<Vec<(usize, &Field)>>();
+        match sort {
+            FieldSort::Bought => (),
+            FieldSort::Readiness => fields.sort_by_key(|(_, f)| (!f.planted(), f.planted().then(|| f.time_to_farm(now, &self.config)))),
+            FieldSort::Earnings => fields.sort_by_key(|(_, f)| std::cmp::Reverse(self.field_earnings(f, now))),
+            FieldSort::Level => fields.sort_by_key(|(_, f)| std::cmp::Reverse(f.level)),
+            FieldSort::Crop => fields.sort_by_key(|(_, f)| f.crop),
+        }
+        fields
+    }
+
+    /// Sells every field, planted or not, and returns the money raised. Growing crops are lost
+    pub fn liquidate(&mut self) -> Money {
+        let fields = std::mem::take(&mut self.fields);
+        let payouts = fields.iter().map(|field| (field.id, self.sell_value(field))).collect::<Vec<(FieldId, Money)>>();
+        let total = payouts.iter().map(|(_, payout)| *payout).sum();
+        self.money += total;
+        for (field, payout) in payouts {
+            self.record(Action::Sell { field, payout }, util::timestamp());
+        }
+        total
+    }
+
+    /// The field with the highest earnings per harvest at `now`, the first one on ties
+    pub fn richest_field(&self, now: u128) -> Option<(usize, &Field)> {
+        self.fields.iter().enumerate().rev().max_by_key(|(_, f)| self.field_earnings(f, now))
+    }
+
+    /// The field with the lowest earnings per harvest at `now`, the first one on ties
+    pub fn poorest_field(&self, now: u128) -> Option<(usize, &Field)> {
+        self.fields.iter().enumerate().min_by_key(|(_, f)| self.field_earnings(f, now))
+    }
+
+    /// Sells the unplanted field worth the least, for raising cash quickly
+    pub fn sell_cheapest_field(&mut self) -> Result<Money> {
+        let id = self.fields.iter().enumerate()
+            .filter(|(_, f)| !f.planted())
+            .min_by_key(|(_, f)| self.sell_value(f))
+            .map(|(i, _)| i as u32)
+            .ok_or(GameError::NoEmptyFields)?;
+        self.sell_field(id)
+    }
+
+    pub fn should_autosave(&self, now: u128, interval_ms: u128) -> bool {
+        now.saturating_sub(self.last_autosave) >= interval_ms
+    }
+
+    /// Does nothing for a sandbox
+    pub fn save_to_path(&mut self, path: String) {
+        if self.is_sandbox { return }
+        let now = util::timestamp();
+        self.record_play_time(now);
+        self.last_autosave = now;
+        self.last_saved = now;
+        for field in self.fields.iter_mut() {
+            field.update_elapsed_growth(now);
+        }
+        let json = self.compact_save();
+        let file = std::fs::File::create(&path).unwrap();
+        // write all to file
+        std::io::Write::write_all(&mut std::io::BufWriter::new(file), json.as_bytes()).unwrap();
+        logging::saved(&path);
+    }
+
+    pub fn load_from_path(path: String) -> Self {
+        Farm::read_from_path(&path).unwrap()
+    }
+
+    /// Like `load_from_path`, but returns an error instead of panicking
+    pub fn read_from_path(path: &str) -> std::result::Result<Self, LoadError> {
+        let result = std::fs::read_to_string(path)
+            .map_err(LoadError::from)
+            .and_then(|contents| Ok(Farm::from_json(&contents)?));
+        if let Err(e) = &result { logging::error("load", e) }
+        let mut farm = result?;
+        farm.resume_growth(util::timestamp());
+        Ok(farm)
+    }
+
+    /// Parses a save, either a `SaveData` or a plain farm from before it. Fields and logged actions with crops
+    /// this version doesn't know are loaded with a placeholder crop, unknown research is dropped, and both are
+    /// reported by `validate`
+    pub fn from_json(json: &str) -> serde_json::Result<Farm> {
+        Farm::parse_value(serde_json::from_str(json)?)
+    }
+
+    /// Like `from_json`, for a save that is already parsed
+    pub fn from_json_value(value: serde_json::Value) -> std::result::Result<Farm, LoadError> {
+        Ok(Farm::parse_value(value)?)
+    }
+
+    fn parse_value(mut value: serde_json::Value) -> serde_json::Result<Farm> {
+        let mut load_issues = Vec::new();
+        let data = if let Some(farm) = value.get_mut("Snapshot") {
+            SaveData::Snapshot(Farm::parse_farm(farm.take())?)
+        } else if let Some(replay) = value.get_mut("Replay") {
+            let start = Farm::parse_farm(replay["start"].take())?;
+            sanitize_actions(&mut replay["actions"], &mut load_issues);
+            SaveData::Replay { start, actions: serde_json::from_value(replay["actions"].take())? }
+        } else {
+            SaveData::Snapshot(Farm::parse_farm(value)?)
+        };
+        let mut farm = Farm::rebuild(data).map_err(serde::de::Error::custom)?;
+        farm.load_issues.extend(load_issues);
+        Ok(farm)
+    }
+
+    fn parse_farm(mut value: serde_json::Value) -> serde_json::Result<Farm> {
+        let mut load_issues = Vec::new();
+        if let Some(fields) = value.get_mut("fields").and_then(|fields| fields.as_array_mut()) {
+            for (i, field) in fields.iter_mut().enumerate() {
+                let Some(crop) = field.get_mut("crop") else { continue };
+                if known_crop(crop) { continue }
+                load_issues.push(ValidationIssue::UnknownCrop { field: i, crop: crop_label(crop), placeholder: UNKNOWN_CROP_PLACEHOLDER });
+                *crop = serde_json::to_value(UNKNOWN_CROP_PLACEHOLDER).unwrap();
+            }
+        }
+        for section in ["seeds", "max_level_bonus"] {
+            let Some(map) = value.get_mut(section).and_then(|map| map.as_object_mut()) else { continue };
+            map.retain(|crop, _| {
+                let known = known_crop(&serde_json::Value::from(crop.as_str()));
+                if !known { load_issues.push(ValidationIssue::UnknownCropDropped { section, crop: crop.clone() }) }
+                known
+            });
+        }
+        if let Some(actions) = value.get_mut("actions") { sanitize_actions(actions, &mut load_issues) }
+
+        let mut farm: Farm = serde_json::from_value(value)?;
+        farm.load_issues = load_issues;
+        farm.assign_missing_ids();
+        Ok(farm)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::CropStats, pricing::StandardPricing, testkit::{self, farm_with_money}};
+
+    /// A fixed time in the past, so tests don't depend on the time of day
+    const T: u128 = 1_000 * util::DAY_MILLIS;
+
+    /// The first day from `T` on when the shop sells `crop`
+    fn day_selling(farm: &Farm, crop: Crop) -> u128 {
+        (0..).map(|day| T + day * util::DAY_MILLIS).find(|now| farm.shop_rotation(*now).contains(&crop)).unwrap()
+    }
+
+    /// A farm with $1,000 and a fresh field of each crop, skipping the crop prerequisites
+    fn farm_with_fields(crops: &[Crop]) -> Farm {
+        let mut farm = farm_with_money(Money::dollars(1_000));
+        for crop in crops {
+            let mut field = Field::new(*crop);
+            field.id = farm.next_field_id();
+            farm.fields.push(field);
+        }
+        farm
+    }
+
+    #[test]
+    fn validate_accepts_a_new_farm() {
+        let mut farm = Farm::new("Test".to_string());
+        farm.buy_field(Crop::Wheat, T).unwrap();
+        assert_eq!(farm.validate(), Ok(()));
+    }
+
+    #[test]
+    fn validate_reports_every_broken_invariant() {
+        let mut farm = Farm::new("Test".to_string());
+        farm.buy_field(Crop::Wheat, T).unwrap();
+        farm.money = Money::dollars(-5);
+        farm.fields[0].level = 9;
+
+        let issues = farm.validate().unwrap_err();
+        assert!(issues.contains(&ValidationIssue::NegativeMoney(Money::dollars(-5))));
+        assert!(issues.contains(&ValidationIssue::LevelOutOfRange { field: 0, level: 9, max: 5 }));
+        assert_eq!(issues.len(), 2);
+    }
+
+    #[test]
+    fn validate_reports_planting_in_the_future() {
+        let mut farm = Farm::new("Test".to_string());
+        farm.buy_field(Crop::Wheat, T).unwrap();
+        farm.fields[0].plant_timestamp = Some(u128::MAX);
+        assert_eq!(farm.validate(), Err(vec![ValidationIssue::PlantedInFuture { field: 0, timestamp: u128::MAX }]));
+    }
+
+    #[test]
+    fn time_to_farm_is_the_full_grow_time_for_a_planting_in_the_future() {
+        let config = GameConfig::default();
+        let mut field = Field::new(Crop::Wheat);
+        assert_eq!(field.time_to_farm(T, &config), field.grow_time(&config));
+        for planted in [T + util::DAY_MILLIS, u128::MAX] {
+            field.plant_timestamp = Some(planted);
+            assert!(field.planted_in_future(T, &config));
+            assert_eq!(field.time_to_farm(T, &config), field.grow_time(&config));
+            assert!(!field.is_ready(T, &config));
+        }
+    }
+
+    #[test]
+    fn every_crop_has_its_own_icon() {
+        let icons = Crop::iter().map(|crop| crop.icon()).collect::<std::collections::HashSet<char>>();
+        assert_eq!(icons.len(), Crop::iter().count());
+    }
+
+    #[test]
+    fn best_investment_suggests_a_first_field() {
+        let farm = Farm::new("Test".to_string());
+        let recommendation = farm.best_investment(T).unwrap();
+        assert_eq!(recommendation.investment, Investment::BuyField(Crop::Wheat));
+        assert_eq!(recommendation.cost, Money::dollars(10));
+    }
+
+    #[test]
+    fn best_investment_prefers_a_cheap_level_up() {
+        let mut farm = Farm::new("Test".to_string());
+        farm.buy_field(Crop::Wheat, T).unwrap();
+        farm.money = Money::dollars(20);
+        let recommendation = farm.best_investment(T).unwrap();
+        assert_eq!(recommendation.investment, Investment::LevelUpField(0));
+        assert_eq!(recommendation.cost, Money::from_cents(250));
+    }
+
+    #[test]
+    fn best_investment_needs_money() {
+        let farm = farm_with_money(Money::dollars(0));
+        assert!(farm.best_investment(T).is_none());
+    }
+
+    #[test]
+    fn a_five_second_session_adds_five_seconds_of_play_time() {
+        let mut farm = Farm::new("Test".to_string());
+        let now = util::timestamp();
+        farm.session_start = now - 5000;
+        farm.record_play_time(now);
+        assert_eq!(farm.total_play_time_ms, 5000);
+        let play_time = farm.play_time().as_millis();
+        assert!((5000..5100).contains(&play_time));
+
+        let loaded: Farm = serde_json::from_str(&serde_json::to_string(&farm).unwrap()).unwrap();
+        assert_eq!(loaded.total_play_time_ms, 5000);
+    }
+
+    #[test]
+    fn crops_are_grouped_into_tiers() {
+        assert_eq!(Crop::Wheat.tier(), Tier::Basic);
+        assert_eq!(Crop::Carrot.tier(), Tier::Advanced);
+        for crop in Crop::iter() {
+            assert_eq!(Tier::iter().filter(|tier| crop.tier() == *tier).count(), 1);
+        }
+    }
+
+    #[test]
+    fn rename_rejects_empty_and_too_long_names() {
+        let mut farm = Farm::new("Test".to_string());
+        assert!(matches!(farm.rename("  ".to_string()), Err(GameError::EmptyName)));
+        assert!(matches!(farm.rename("a".repeat(MAX_NAME_LENGTH + 1)), Err(GameError::NameTooLong)));
+        assert_eq!(farm.name, "Test");
+        farm.rename(" Sunny Acres ".to_string()).unwrap();
+        assert_eq!(farm.name, "Sunny Acres");
+    }
+
+    #[test]
+    fn daily_bonus_is_claimable_once_per_day() {
+        let mut farm = farm_with_money(Money::dollars(0));
+        let day_one = 1_000 * util::DAY_MILLIS;
+        let bonus = farm.claim_daily_bonus(day_one).unwrap();
+        assert!((Money::dollars(DAILY_BONUS_MIN as i64)..=Money::dollars(DAILY_BONUS_MAX as i64)).contains(&bonus));
+        assert!(matches!(farm.claim_daily_bonus(day_one + util::DAY_MILLIS - 1), Err(GameError::BonusAlreadyClaimed)));
+        assert!(farm.claim_daily_bonus(day_one + util::DAY_MILLIS).is_ok());
+        assert!(farm.money >= Money::dollars(2 * DAILY_BONUS_MIN as i64));
+    }
+
+    #[test]
+    fn crop_loss_only_pays_when_insured() {
+        let mut farm = farm_with_money(Money::dollars(100));
+        farm.buy_field(Crop::Wheat, T).unwrap();
+        farm.buy_field(Crop::Wheat, T).unwrap();
+        farm.fields[0].plant_timestamp = Some(0);
+        farm.fields[1].plant_timestamp = Some(0);
+        farm.buy_insurance(0).unwrap();
+        assert!(matches!(farm.buy_insurance(0), Err(GameError::AlreadyInsured)));
+
+        let money = farm.money;
+        let earnings = farm.fields[0].earnings(farm.prices());
+        assert_eq!(farm.crop_loss(0).unwrap(), earnings);
+        assert_eq!(farm.money, money + earnings);
+        assert!(!farm.fields[0].insured);
+        assert_eq!(farm.crop_loss(1).unwrap(), Money::ZERO);
+        assert_eq!(farm.money, money + earnings);
+        assert!(matches!(farm.crop_loss(1), Err(GameError::NotPlanted)));
+    }
+
+    #[test]
+    fn storms_pay_out_insurance() {
+        let mut farm = Farm::new("Test".to_string());
+        farm.buy_field(Crop::Wheat, T).unwrap();
+        farm.fields[0].plant_timestamp = Some(0);
+        farm.buy_insurance(0).unwrap();
+        let earnings = farm.fields[0].earnings(farm.prices());
+
+        let field = farm.fields[0].id;
+        let mut now = 1_000 * util::DAY_MILLIS;
+        let loss = loop {
+            now += STORM_INTERVAL_MS;
+            let loss = farm.tick(now).into_iter().find(|event| matches!(event, GameEvent::CropLost { .. }));
+            if let Some(loss) = loss { break loss }
+        };
+        assert_eq!(loss, GameEvent::CropLost { field, crop: Crop::Wheat, payout: earnings });
+        assert!(!farm.fields[0].planted());
+        assert!(!farm.fields[0].insured);
+        assert_eq!(farm.tick(now), vec![]);
+    }
+
+    #[test]
+    fn tick_reports_a_ready_field_once() {
+        let mut farm = Farm::new("Test".to_string());
+        farm.seed = 42;
+        farm.buy_field(Crop::Wheat, T).unwrap();
+        let planted = 1_000 * util::DAY_MILLIS;
+        farm.fields[0].plant_timestamp = Some(planted);
+        let field = farm.fields[0].id;
+        let ready = planted + farm.fields[0].grow_time(farm.config());
+
+        assert!(!farm.tick(ready - 1).contains(&GameEvent::FieldReady { field, crop: Crop::Wheat }));
+        assert!(farm.tick(ready).contains(&GameEvent::FieldReady { field, crop: Crop::Wheat }));
+        assert!(!farm.tick(ready + 1).contains(&GameEvent::FieldReady { field, crop: Crop::Wheat }));
+    }
+
+    #[test]
+    fn age_is_the_time_since_planting() {
+        let mut field = Field::new(Crop::Wheat);
+        let now = 1_000 * util::DAY_MILLIS;
+        assert_eq!(field.age(now), None);
+        field.plant_timestamp = Some(now - 10_000);
+        assert_eq!(field.age(now), Some(10_000));
+    }
+
+    #[test]
+    fn restore_undoes_an_action() {
+        let mut farm = Farm::new("Test".to_string());
+        let snapshot = farm.snapshot();
+        let before = serde_json::to_string(&farm).unwrap();
+        farm.buy_field(Crop::Wheat, T).unwrap();
+        farm.restore(snapshot);
+        assert_eq!(serde_json::to_string(&farm).unwrap(), before);
+    }
+
+    #[test]
+    fn sell_refund_follows_the_rate() {
+        let refund = |rate: f64| {
+            let mut farm = Farm::new("Test".to_string());
+            farm.buy_field(Crop::Wheat, T).unwrap();
+            farm.set_sell_refund_rate(rate).unwrap();
+            farm.sell_field(0).unwrap()
+        };
+        assert_eq!(refund(0.8), refund(0.2) * 4.);
+        assert_eq!(refund(1.), Crop::Wheat.get_new_field_price());
+
+        let mut farm = Farm::new("Test".to_string());
+        assert!(matches!(farm.set_sell_refund_rate(1.5), Err(GameError::InvalidRate)));
+        assert_eq!(farm.sell_refund_rate, DEFAULT_SELL_REFUND_RATE);
+    }
+
+    #[test]
+    fn easy_starts_with_better_terms_than_hard() {
+        let easy = Farm::new_with_difficulty("Easy".to_string(), Difficulty::Easy);
+        let hard = Farm::new_with_difficulty("Hard".to_string(), Difficulty::Hard);
+        assert!(easy.money > hard.money);
+        assert!(easy.sell_refund_rate > hard.sell_refund_rate);
+        assert_eq!(hard.difficulty, Difficulty::Hard);
+    }
+
+    #[test]
+    fn csv_export_has_a_row_per_field() {
+        let farm = farm_with_fields(&[Crop::Wheat, Crop::Potato]);
+        let csv = farm.export_csv();
+        let lines = csv.lines().collect::<Vec<&str>>();
+        assert_eq!(lines[0], "index,crop,level,planted,time_remaining,earnings,sell_value");
+        assert_eq!(lines[1], format!("1,Wheat,1,false,,{:.2},{:.2}", farm.fields[0].earnings(farm.prices()).as_dollars(), farm.sell_value(&farm.fields[0]).as_dollars()));
+        assert_eq!(lines.len(), 3);
+    }
+
+    #[test]
+    fn harvest_crop_only_harvests_that_crop() {
+        let mut farm = farm_with_fields(&[Crop::Wheat, Crop::Potato, Crop::Wheat, Crop::Wheat]);
+        let now = 1_000 * util::DAY_MILLIS;
+        for field in farm.fields.iter_mut() {
+            field.plant_timestamp = Some(0);
+        }
+        farm.fields[3].plant_timestamp = Some(now);
+        let earnings = farm.fields[0].earnings(farm.prices());
+        let money = farm.money;
+        assert_eq!(farm.harvest_crop(Crop::Wheat, now), earnings + earnings);
+        assert_eq!(farm.money, money + earnings + earnings);
+        assert!(farm.fields[1].planted() && farm.fields[3].planted());
+        assert!(!farm.fields[0].planted() && !farm.fields[2].planted());
+    }
+
+    #[test]
+    fn max_earnings_is_the_earnings_at_the_max_level() {
+        let config = GameConfig::default();
+        let prices = Prices::new(&StandardPricing, &config);
+        let field = Field::new(Crop::Wheat);
+        assert_eq!(config.max_level(Crop::Wheat), 5);
+        assert_eq!(field.max_earnings(prices), field.earnings_at_level(prices, 5));
+        assert!(field.max_earnings(prices) > field.earnings(prices));
+    }
+
+    #[test]
+    fn sell_cheapest_field_skips_planted_fields() {
+        let mut farm = farm_with_fields(&[Crop::Potato, Crop::Wheat, Crop::Potato, Crop::Wheat]);
+        farm.fields[1].plant_timestamp = Some(0);
+        let price = farm.sell_value(&farm.fields[3]);
+        assert_eq!(farm.sell_cheapest_field().unwrap(), price);
+        assert_eq!(farm.fields.iter().map(|f| f.crop).collect::<Vec<Crop>>(), [Crop::Potato, Crop::Wheat, Crop::Potato]);
+
+        farm.fields.retain(|f| f.planted());
+        assert!(matches!(farm.sell_cheapest_field(), Err(GameError::NoEmptyFields)));
+    }
+
+    #[test]
+    fn auto_replant_only_replants_with_a_seed() {
+        let mut farm = farm_with_fields(&[Crop::Potato]);
+        assert!(farm.toggle_auto_replant(0).unwrap());
+        let earnings = farm.fields[0].earnings(farm.prices());
+        farm.buy_seeds(Crop::Potato, 1).unwrap();
+        let money = farm.money;
+
+        farm.fields[0].plant_timestamp = Some(0);
+        assert_eq!(farm.farm_field(0).unwrap(), (earnings, true));
+        assert!(farm.fields[0].planted());
+        assert_eq!(farm.seed_count(Crop::Potato), 0);
+        assert_eq!(farm.money, money + earnings);
+
+        let earnings = farm.fields[0].earnings(farm.prices());
+        farm.fields[0].plant_timestamp = Some(0);
+        assert_eq!(farm.farm_field(0).unwrap(), (earnings, false));
+        assert!(!farm.fields[0].planted());
+    }
+
+    #[test]
+    fn field_ids_stay_stable_after_selling_an_earlier_field() {
+        let mut farm = farm_with_fields(&[Crop::Wheat, Crop::Potato, Crop::Wheat]);
+        let (first, potato, last) = (farm.field_id(0).unwrap(), farm.field_id(1).unwrap(), farm.field_id(2).unwrap());
+        assert!(first != potato && potato != last);
+
+        farm.sell_field_by_id(first).unwrap();
+        assert_eq!(farm.field(potato).unwrap().crop, Crop::Potato);
+        assert_eq!(farm.index_of(last).unwrap(), 1);
+        farm.level_up_field_by_id(potato).unwrap();
+        assert_eq!(farm.field(potato).unwrap().level, 2);
+        farm.buy_seeds(Crop::Wheat, 1).unwrap();
+        farm.plant_field_by_id(last).unwrap();
+        assert!(farm.field(last).unwrap().planted());
+        assert!(matches!(farm.plant_field_by_id(first), Err(GameError::UnknownField)));
+
+        farm.buy_field(Crop::Wheat, T).unwrap();
+        assert!(farm.fields.iter().all(|f| f.id != first));
+    }
+
+    #[test]
+    fn earnings_rate_is_earnings_over_grow_time() {
+        let mut farm = Farm::new("Test".to_string());
+        farm.buy_field(Crop::Wheat, T).unwrap();
+        farm.buy_field(Crop::Wheat, T).unwrap();
+        assert_eq!(farm.earnings_rate_per_second(), Money::ZERO);
+
+        farm.fields[0].plant_timestamp = Some(0);
+        let earnings = farm.fields[0].earnings(farm.prices());
+        assert_eq!(farm.fields[0].grow_time(farm.config()), 100_000);
+        assert_eq!(farm.earnings_rate_per_second(), earnings * 0.01);
+    }
+
+    #[test]
+    fn count_of_and_owns_all_crops() {
+        let farm = farm_with_fields(&[Crop::Wheat, Crop::Wheat]);
+        assert_eq!(farm.count_of(Crop::Wheat), 2);
+        assert_eq!(farm.count_of(Crop::Potato), 0);
+        assert!(!farm.owns_all_crops());
+        assert!(farm_with_fields(&[Crop::Wheat, Crop::Potato, Crop::Carrot, Crop::Tomato]).owns_all_crops());
+    }
+
+    #[test]
+    fn crop_names_are_plain_ascii() {
+        for crop in Crop::iter() {
+            let name = crop.name();
+            assert!(name.chars().all(|c| c.is_ascii_alphabetic()), "{name:?}");
+            assert_eq!(name, format!("{crop:?}"));
+        }
+    }
+
+    #[test]
+    fn buy_fields_buys_none_unless_all_are_affordable() {
+        let mut farm = farm_with_money(Money::dollars(25));
+        assert!(matches!(farm.buy_fields(Crop::Wheat, 3, T), Err(GameError::InsufficientFunds)));
+        assert!(farm.fields.is_empty());
+        assert_eq!(farm.money, Money::dollars(25));
+
+        assert_eq!(farm.buy_fields(Crop::Wheat, 2, T).unwrap(), vec![0, 1]);
+        assert_eq!(farm.money, Money::dollars(5));
+        assert_ne!(farm.fields[0].id, farm.fields[1].id);
+    }
+
+    #[test]
+    fn buy_fields_respects_the_field_limit() {
+        let mut farm = farm_with_money(Money::dollars(1_000));
+        assert!(matches!(farm.buy_fields(Crop::Wheat, MAX_FIELDS + 1, T), Err(GameError::MaxFieldsReached)));
+        assert!(farm.fields.is_empty());
+        farm.buy_fields(Crop::Wheat, MAX_FIELDS, T).unwrap();
+        assert_eq!(farm.fields.len(), MAX_FIELDS);
+    }
+
+    #[test]
+    fn converting_changes_the_crop_and_resets_the_level() {
+        let mut farm = farm_with_fields(&[Crop::Wheat, Crop::Wheat]);
+        farm.fields[0].level = Crop::Wheat.get_max_level();
+        farm.fields[1].level = 3;
+        assert!(matches!(farm.convert_field(1, Crop::Wheat), Err(GameError::SameCrop)));
+
+        assert_eq!(farm.convert_field(1, Crop::Potato).unwrap(), Money::dollars(50));
+        assert_eq!(farm.money, Money::dollars(950));
+        assert_eq!((farm.fields[1].crop, farm.fields[1].level), (Crop::Potato, 1));
+    }
+
+    #[test]
+    fn converting_a_planted_field_fails() {
+        let mut farm = farm_with_fields(&[Crop::Wheat, Crop::Wheat]);
+        farm.fields[0].level = Crop::Wheat.get_max_level();
+        farm.buy_seeds(Crop::Wheat, 1).unwrap();
+        farm.plant_field(1).unwrap();
+        let money = farm.money;
+        assert!(matches!(farm.convert_field(1, Crop::Potato), Err(GameError::AlreadyPlanted)));
+        assert_eq!(farm.fields[1].crop, Crop::Wheat);
+        assert_eq!(farm.money, money);
+    }
+
+    #[test]
+    fn advance_makes_a_planted_field_ready() {
+        let mut farm = farm_with_money(Money::dollars(100));
+        farm.buy_field(Crop::Wheat, T).unwrap();
+        farm.buy_seeds(Crop::Wheat, 1).unwrap();
+        farm.plant_field(0).unwrap();
+        assert!(matches!(farm.farm_field(0), Err(GameError::NotYetReady)));
+
+        let grow_time = farm.fields[0].grow_time(farm.config());
+        testkit::advance(&mut farm, grow_time);
+        assert!(farm.farm_field(0).is_ok());
+    }
+
+    #[test]
+    fn advance_makes_the_daily_bonus_claimable_again() {
+        let mut farm = farm_with_money(Money::dollars(0));
+        farm.claim_daily_bonus(util::timestamp()).unwrap();
+        assert!(matches!(farm.claim_daily_bonus(util::timestamp()), Err(GameError::BonusAlreadyClaimed)));
+
+        testkit::advance(&mut farm, util::DAY_MILLIS);
+        assert!(farm.claim_daily_bonus(util::timestamp()).is_ok());
+    }
+
+    #[test]
+    fn interest_is_paid_once_an_interval_has_passed() {
+        let mut farm = farm_with_money(Money::dollars(1_000));
+        farm.interest_rate = 0.1;
+        let start = 1_000 * util::DAY_MILLIS;
+        farm.last_interest = start;
+
+        assert_eq!(farm.apply_interest(start + INTEREST_INTERVAL_MS - 1), Money::ZERO);
+        assert_eq!(farm.money, Money::dollars(1_000));
+        assert_eq!(farm.apply_interest(start + INTEREST_INTERVAL_MS), Money::dollars(100));
+        assert_eq!(farm.money, Money::dollars(1_100));
+    }
+
+    #[test]
+    fn advance_makes_interest_due() {
+        let mut farm = farm_with_money(Money::dollars(1_000));
+        farm.interest_rate = 0.1;
+        assert_eq!(farm.apply_interest(util::timestamp()), Money::ZERO);
+
+        testkit::advance(&mut farm, INTEREST_INTERVAL_MS);
+        assert_eq!(farm.apply_interest(util::timestamp()), Money::dollars(100));
+    }
+
+    #[test]
+    fn earnings_saturate_at_very_high_levels() {
+        let mut config = GameConfig::default();
+        config.crops.insert(Crop::Tomato, CropStats { max_level: Some(Level::MAX), ..Default::default() });
+        let prices = Prices::new(&StandardPricing, &config);
+        let mut field = Field::new(Crop::Tomato);
+        field.level = Level::MAX;
+        assert_eq!(field.earnings(prices), Money::MAX);
+        assert_eq!(field.max_earnings(prices), Money::MAX);
+    }
+
+    #[test]
+    fn level_display_never_exceeds_the_max() {
+        let mut field = Field::new(Crop::Tomato);
+        assert_eq!(field.level_display(50), "1/50");
+        field.level = Level::MAX;
+        assert_eq!(field.level_display(50), "50/50");
+    }
+
+    #[test]
+    fn replay_lists_actions_in_order() {
+        let mut farm = farm_with_money(Money::dollars(100));
+        farm.buy_field(Crop::Wheat, T).unwrap();
+        assert!(farm.actions.is_empty());
+
+        farm.record_actions = true;
+        farm.buy_field(Crop::Wheat, T).unwrap();
+        farm.buy_seeds(Crop::Wheat, 1).unwrap();
+        farm.plant_field(1).unwrap();
+        let field = farm.fields[1].id;
+        let replay = serde_json::from_str::<Vec<TimestampedAction>>(&farm.export_replay()).unwrap();
+        let actions = replay.iter().map(|a| a.action).collect::<Vec<Action>>();
+        assert_eq!(actions, vec![
+            Action::Buy { field, crop: Crop::Wheat, cost: Money::dollars(10) },
+            Action::BuySeeds { crop: Crop::Wheat, count: 1, cost: Money::dollars(1) },
+            Action::Plant { field, cost: Money::ZERO },
+        ]);
+    }
+
+    #[test]
+    fn merge_adds_the_other_farms_fields_and_money() {
+        let mut farm = farm_with_money(Money::dollars(100));
+        farm.buy_field(Crop::Wheat, T).unwrap();
+        let mut other = farm_with_fields(&[Crop::Potato, Crop::Potato]);
+        other.money = Money::dollars(300);
+
+        farm.merge(&other).unwrap();
+        assert_eq!(farm.money, Money::dollars(390));
+        assert_eq!(farm.fields.iter().map(|f| f.crop).collect::<Vec<Crop>>(), vec![Crop::Wheat, Crop::Potato, Crop::Potato]);
+        assert!(farm.validate().is_ok());
+    }
+
+    #[test]
+    fn merge_beyond_the_field_limit_changes_nothing() {
+        let mut farm = farm_with_money(Money::dollars(1_000));
+        farm.buy_fields(Crop::Wheat, MAX_FIELDS - 1, T).unwrap();
+        let mut other = farm_with_money(Money::dollars(1_000));
+        other.buy_fields(Crop::Wheat, 2, T).unwrap();
+        let money = farm.money;
+
+        assert!(matches!(farm.merge(&other), Err(GameError::MaxFieldsReached)));
+        assert_eq!(farm.fields.len(), MAX_FIELDS - 1);
+        assert_eq!(farm.money, money);
+    }
+
+    #[test]
+    fn prestige_preview_needs_the_threshold() {
+        let farm = farm_with_money(PRESTIGE_THRESHOLD - Money::from_cents(1));
+        assert!(!farm.can_prestige());
+        assert_eq!(farm.prestige_preview(), None);
+
+        let farm = farm_with_money(PRESTIGE_THRESHOLD * 4.);
+        assert!(farm.can_prestige());
+        assert_eq!(farm.prestige_preview(), Some(1.2));
+    }
+
+    #[test]
+    fn harvested_soil_rests_before_replanting() {
+        let mut farm = farm_with_money(Money::dollars(100));
+        farm.buy_field(Crop::Wheat, T).unwrap();
+        farm.buy_seeds(Crop::Wheat, 2).unwrap();
+        farm.plant_field(0).unwrap();
+        let grow_time = farm.fields[0].grow_time(farm.config());
+        testkit::advance(&mut farm, grow_time);
+        farm.farm_field(0).unwrap();
+        assert!(matches!(farm.plant_field(0), Err(GameError::SoilResting)));
+
+        let rest_time = farm.config().rest_time(Crop::Wheat);
+        testkit::advance(&mut farm, rest_time);
+        assert!(farm.plant_field(0).is_ok());
+    }
+
+    #[test]
+    fn auto_replanted_fields_start_growing_after_the_rest() {
+        let mut farm = farm_with_money(Money::dollars(100));
+        farm.buy_field(Crop::Wheat, T).unwrap();
+        farm.buy_seeds(Crop::Wheat, 1).unwrap();
+        farm.toggle_auto_replant(0).unwrap();
+        farm.fields[0].plant_timestamp = Some(0);
+        let rest = farm.config().rest_time(Crop::Wheat);
+
+        let harvested = util::timestamp();
+        assert!(farm.farm_field(0).unwrap().1);
+        let planted = farm.fields[0].plant_timestamp.unwrap();
+        assert!(planted >= harvested + rest);
+        assert_eq!(farm.fields[0].time_to_farm(planted, farm.config()), farm.fields[0].grow_time(farm.config()));
+        assert_eq!(farm.fields[0].age(harvested), Some(0));
+        assert!(farm.validate().is_ok());
+    }
+
+    #[test]
+    fn validate_allows_plantings_up_to_the_rest_time_ahead() {
+        let mut farm = farm_with_money(Money::dollars(100));
+        farm.buy_field(Crop::Wheat, T).unwrap();
+        let ahead = util::timestamp() + farm.config().rest_time(Crop::Wheat) / 2;
+        farm.fields[0].plant_timestamp = Some(ahead);
+        assert!(farm.validate().is_ok());
+    }
+
+    #[test]
+    fn crops_need_a_max_level_field_of_their_prerequisite() {
+        let mut farm = farm_with_fields(&[Crop::Wheat]);
+        let now = day_selling(&farm, Crop::Potato);
+        assert!(matches!(farm.buy_field(Crop::Potato, now), Err(GameError::PrerequisiteNotMet)));
+        assert!(matches!(farm.convert_field(0, Crop::Potato), Err(GameError::PrerequisiteNotMet)));
+        assert_eq!(farm.money, Money::dollars(1_000));
+
+        farm.fields[0].level = Crop::Wheat.get_max_level();
+        farm.buy_field(Crop::Potato, now).unwrap();
+        assert_eq!(farm.fields[1].crop, Crop::Potato);
+        assert_eq!(farm.money, Money::dollars(900));
+        assert!(!farm.prerequisite_met(Crop::Carrot));
+    }
+
+    #[test]
+    fn summary_matches_the_getters() {
+        let mut farm = farm_with_fields(&[Crop::Wheat, Crop::Potato]);
+        farm.fields[0].plant_timestamp = Some(0);
+        let summary = farm.summary();
+        assert_eq!(summary, FarmSummary {
+            name: farm.name.clone(),
+            money: farm.money,
+            field_count: 2,
+            ready_count: farm.ready_count(util::timestamp()),
+            net_worth: farm.net_worth(),
+            earnings_rate: farm.earnings_rate_per_second(),
+        });
+        assert_eq!(summary.ready_count, 1);
+    }
+
+    #[test]
+    fn is_ready_once_planted_and_grown() {
+        let config = GameConfig::default();
+        let mut field = Field::new(Crop::Wheat);
+        let planted = 1_000 * util::DAY_MILLIS;
+        assert!(!field.is_ready(planted, &config));
+
+        field.plant_timestamp = Some(planted);
+        let grow_time = field.grow_time(&config);
+        assert!(!field.is_ready(planted + grow_time - 1, &config));
+        assert!(field.is_ready(planted + grow_time, &config));
+    }
+
+    #[test]
+    fn shop_rotation_is_stable_within_a_day() {
+        let mut farm = farm_with_money(Money::dollars(1_000));
+        farm.seed = 42;
+        assert_eq!(farm.shop_rotation(T), farm.shop_rotation(T + util::DAY_MILLIS - 1));
+        assert!((1..10).any(|day| farm.shop_rotation(T + day * util::DAY_MILLIS) != farm.shop_rotation(T)));
+        assert!((0..10).all(|day| farm.shop_rotation(T + day * util::DAY_MILLIS).contains(&Crop::Wheat)));
+    }
+
+    #[test]
+    fn buying_needs_the_crop_in_todays_rotation() {
+        let mut farm = farm_with_fields(&[Crop::Wheat]);
+        farm.fields[0].level = Crop::Wheat.get_max_level();
+        let closed = (0..).map(|day| T + day * util::DAY_MILLIS).find(|now| !farm.shop_rotation(*now).contains(&Crop::Potato)).unwrap();
+        assert_eq!(farm.buy_field(Crop::Potato, closed), Err(GameError::NotInRotation));
+        assert!(farm.buy_field(Crop::Potato, day_selling(&farm, Crop::Potato)).is_ok());
+    }
+
+    #[test]
+    fn market_crash_halves_earnings_until_it_ends() {
+        let mut farm = farm_with_fields(&[Crop::Wheat]);
+        let normal = farm.field_earnings(&farm.fields[0], T);
+        farm.market_event = Some(MarketEvent { multiplier: 0.5, ends_at: T + MARKET_INTERVAL_MS });
+        assert_eq!(farm.field_earnings(&farm.fields[0], T), normal * 0.5);
+        assert_eq!(farm.field_earnings(&farm.fields[0], T + MARKET_INTERVAL_MS), normal);
+
+        assert!(farm.tick(T + MARKET_INTERVAL_MS).contains(&GameEvent::MarketRecovered));
+        assert_eq!(farm.market_event, None);
+    }
+
+    #[test]
+    fn harvests_pay_the_crashed_price() {
+        let mut farm = farm_with_fields(&[Crop::Wheat, Crop::Wheat]);
+        let now = util::timestamp();
+        let normal = farm.field_earnings(&farm.fields[0], now);
+        farm.market_event = Some(MarketEvent { multiplier: 0.5, ends_at: now + MARKET_INTERVAL_MS });
+        farm.fields[0].plant_timestamp = Some(0);
+        farm.fields[1].plant_timestamp = Some(0);
+        assert_eq!(farm.farm_field(0).unwrap().0, normal * 0.5);
+        assert_eq!(farm.harvest_crop(Crop::Wheat, now), normal * 0.5);
+    }
+
+    #[test]
+    fn leveled_fields_sell_for_more() {
+        let config = GameConfig::default();
+        let prices = Prices::new(&StandardPricing, &config);
+        let fresh = Field::new(Crop::Wheat);
+        let mut leveled = Field::new(Crop::Wheat);
+        leveled.level = 3;
+        let level_ups = config.next_level_price(Crop::Wheat, 1) + config.next_level_price(Crop::Wheat, 2);
+        assert_eq!(leveled.invested(prices), fresh.invested(prices) + level_ups);
+        assert!(leveled.sell_value(prices, 0.5) > fresh.sell_value(prices, 0.5));
+        assert_eq!(fresh.sell_value(prices, 0.5), Money::dollars(5));
+    }
+
+    #[test]
+    fn greenhouse_fields_are_ready_sooner() {
+        let mut farm = farm_with_fields(&[Crop::Wheat, Crop::Wheat]);
+        farm.buy_greenhouse(1).unwrap();
+        assert_eq!(farm.buy_greenhouse(1), Err(GameError::AlreadyUpgraded));
+        for field in farm.fields.iter_mut() {
+            field.plant_timestamp = Some(T);
+        }
+
+        let plain = T + farm.fields[0].grow_time(farm.config());
+        let greenhouse = T + farm.fields[1].grow_time(farm.config());
+        assert!(greenhouse < plain);
+        assert!(farm.fields[1].is_ready(greenhouse, farm.config()));
+        assert!(!farm.fields[0].is_ready(greenhouse, farm.config()));
+    }
+
+    #[test]
+    fn previews_match_the_actions_without_changing_the_farm() {
+        let mut farm = farm_with_fields(&[Crop::Wheat]);
+        let fields = serde_json::to_string(&farm.fields).unwrap();
+        assert_eq!(farm.preview_buy(Crop::Wheat, T), Ok(Money::dollars(10)));
+        assert_eq!(farm.preview_level_up(0), Ok(Money::from_cents(250)));
+        assert_eq!(farm.preview_plant(0), Err(GameError::NoSeeds));
+        assert_eq!(farm.preview_plant(1), Err(GameError::OutOfBounds));
+        assert_eq!(farm.preview_sell(0), Ok(Money::dollars(5)));
+        assert_eq!(farm.money, Money::dollars(1_000));
+        assert_eq!(serde_json::to_string(&farm.fields).unwrap(), fields);
+
+        let money = farm.money;
+        farm.level_up_field(0).unwrap();
+        assert_eq!(farm.money, money - Money::from_cents(250));
+        farm.buy_seeds(Crop::Wheat, 1).unwrap();
+        assert_eq!(farm.preview_plant(0), Ok(()));
+        farm.plant_field(0).unwrap();
+        assert_eq!(farm.preview_plant(0), Err(GameError::AlreadyPlanted));
+        assert_eq!(farm.plant_field(0), Err(GameError::AlreadyPlanted));
+
+        let mut broke = farm_with_money(Money::ZERO);
+        assert_eq!(broke.preview_buy(Crop::Wheat, T), Err(GameError::InsufficientFunds));
+        assert_eq!(broke.buy_field(Crop::Wheat, T), Err(GameError::InsufficientFunds));
+    }
+
+    #[test]
+    fn cheap_upgrades_lower_level_up_prices() {
+        let mut farm = farm_with_fields(&[Crop::Wheat]);
+        assert_eq!(farm.unlock_skill(Skill::CheapUpgrades), Err(GameError::NotEnoughXp));
+        farm.xp = Skill::CheapUpgrades.xp_cost();
+        let price = farm.level_up_price(&farm.fields[0]).unwrap();
+
+        farm.unlock_skill(Skill::CheapUpgrades).unwrap();
+        assert_eq!(farm.level_up_price(&farm.fields[0]), Ok(price * CHEAP_UPGRADES_MULTIPLIER));
+        assert_eq!(farm.xp, 0);
+        assert_eq!(farm.unlock_skill(Skill::CheapUpgrades), Err(GameError::SkillAlreadyUnlocked));
+
+        let money = farm.money;
+        farm.level_up_field(0).unwrap();
+        assert_eq!(farm.money, money - price * CHEAP_UPGRADES_MULTIPLIER);
+    }
+
+    #[test]
+    fn richest_and_poorest_fields_go_by_earnings() {
+        let mut farm = farm_with_fields(&[Crop::Wheat, Crop::Wheat, Crop::Carrot, Crop::Wheat]);
+        farm.fields[1].level = 3;
+        assert_eq!(farm.richest_field(T).map(|(i, _)| i), Some(2));
+        assert_eq!(farm.poorest_field(T).map(|(i, _)| i), Some(0));
+
+        farm.fields[0].level = 3;
+        assert_eq!(farm.poorest_field(T).map(|(i, _)| i), Some(3));
+        assert!(farm_with_money(Money::ZERO).richest_field(T).is_none());
+        assert!(farm_with_money(Money::ZERO).poorest_field(T).is_none());
+    }
+
+    /// Carrot level 1 empty, Wheat level 3 planted at `T`, Wheat level 1 planted before it, Wheat level 2 empty
+    fn farm_to_sort() -> Farm {
+        let mut farm = farm_with_fields(&[Crop::Carrot, Crop::Wheat, Crop::Wheat, Crop::Wheat]);
+        farm.fields[1].level = 3;
+        farm.fields[1].plant_timestamp = Some(T);
+        farm.fields[2].plant_timestamp = Some(T - 1);
+        farm.fields[3].level = 2;
+        farm
+    }
+
+    fn sorted_indices(farm: &Farm, sort: FieldSort) -> Vec<usize> {
+        farm.fields_sorted(sort, T).into_iter().map(|(i, _)| i).collect()
+    }
+
+    #[test]
+    fn fields_sorted_by_bought_keeps_the_order() {
+        assert_eq!(sorted_indices(&farm_to_sort(), FieldSort::Bought), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn fields_sorted_by_readiness_puts_the_soonest_first() {
+        assert_eq!(sorted_indices(&farm_to_sort(), FieldSort::Readiness), vec![2, 1, 0, 3]);
+    }
+
+    #[test]
+    fn fields_sorted_by_earnings_puts_the_highest_first() {
+        assert_eq!(sorted_indices(&farm_to_sort(), FieldSort::Earnings), vec![0, 1, 3, 2]);
+    }
+
+    #[test]
+    fn fields_sorted_by_level_puts_the_highest_first() {
+        assert_eq!(sorted_indices(&farm_to_sort(), FieldSort::Level), vec![1, 3, 0, 2]);
+    }
+
+    #[test]
+    fn fields_sorted_by_crop_groups_crops() {
+        assert_eq!(sorted_indices(&farm_to_sort(), FieldSort::Crop), vec![1, 2, 3, 0]);
+    }
+
+    #[test]
+    fn tutorial_follows_the_first_actions() {
+        let mut farm = farm_with_money(Money::dollars(1_000));
+        assert_eq!(farm.tutorial_step, Some(0));
+        farm.advance_tutorial(&Action::Plant { field: FieldId(0), cost: Money::ZERO });
+        assert_eq!(farm.tutorial_step, Some(0));
+        farm.buy_field(Crop::Wheat, T).unwrap();
+        assert_eq!(farm.tutorial_step, Some(1));
+        farm.buy_seeds(Crop::Wheat, 1).unwrap();
+        assert_eq!(farm.tutorial_step, Some(1));
+        farm.plant_field(0).unwrap();
+        assert_eq!(farm.tutorial_step, Some(2));
+        farm.advance_tutorial(&Action::Harvest { field: farm.fields[0].id, payout: Money::ZERO });
+        assert_eq!(farm.tutorial_step, None);
+        assert_eq!(farm.tutorial_prompt(), None);
+    }
+
+    #[test]
+    fn expected_profit_compares_wheat_and_carrot() {
+        let config = GameConfig::default();
+        let prices = Prices::new(&StandardPricing, &config);
+        let (wheat, carrot) = (Field::new(Crop::Wheat), Field::new(Crop::Carrot));
+        let carrot_cycle = carrot.grow_time(&config) + config.rest_time(Crop::Carrot);
+        assert_eq!(wheat.expected_profit(prices, carrot_cycle * 2), Money::dollars(10));
+        assert_eq!(carrot.expected_profit(prices, carrot_cycle * 2), Money::dollars(200));
+        assert_eq!(carrot.expected_profit(prices, carrot_cycle - 1), Money::ZERO);
+    }
+
+    #[test]
+    fn farm_names_are_sanitized() {
+        assert_eq!(Farm::validate_name("  \x1b[1;31mRed Acres\x1b[0m\n"), Ok("Red Acres".to_string()));
+        assert_eq!(Farm::validate_name("\x1b[0m \t"), Err(GameError::EmptyName));
+    }
+
+    #[test]
+    fn harvest_result_describes_the_harvested_field() {
+        let mut farm = farm_with_fields(&[Crop::Wheat, Crop::Carrot]);
+        farm.fields[1].level = 2;
+        farm.fields[1].plant_timestamp = Some(T);
+        let now = T + farm.fields[1].grow_time(farm.config());
+        let payout = farm.field_earnings(&farm.fields[1], now);
+
+        let result = farm.harvest(1, now).unwrap();
+        assert_eq!(result, HarvestResult {
+            field: farm.fields[1].id,
+            crop: Crop::Carrot,
+            level: 2,
+            payout,
+            balance: Money::dollars(1_000) + payout,
+            replanted: false,
+        });
+        assert_eq!(farm.money, result.balance);
+    }
+
+    #[test]
+    fn autosave_waits_for_the_interval() {
+        let mut farm = farm_with_money(Money::ZERO);
+        farm.last_autosave = T;
+        assert!(!farm.should_autosave(T + 999, 1_000));
+        assert!(farm.should_autosave(T + 1_000, 1_000));
+    }
+
+    #[test]
+    fn research_lets_fields_level_past_the_base_max() {
+        let mut farm = farm_with_fields(&[Crop::Wheat]);
+        let base_max = farm.config().max_level(Crop::Wheat);
+        farm.fields[0].level = base_max;
+        assert_eq!(farm.level_up_field(0), Err(GameError::MaxLevelReached));
+
+        let price = farm.research_price(Crop::Wheat);
+        farm.research_max_level(Crop::Wheat).unwrap();
+        assert_eq!(farm.money, Money::dollars(1_000) - price);
+        assert_eq!(farm.max_level(Crop::Wheat), base_max + 1);
+        assert!(farm.research_price(Crop::Wheat) > price);
+
+        farm.level_up_field(0).unwrap();
+        assert_eq!(farm.fields[0].level, base_max + 1);
+        assert_eq!(farm.level_up_field(0), Err(GameError::MaxLevelReached));
+    }
+
+    #[test]
+    fn status_line_contains_its_parts() {
+        let mut farm = farm_with_fields(&[Crop::Wheat, Crop::Wheat, Crop::Wheat]);
+        farm.name = "Bob".to_string();
+        farm.fields[0].plant_timestamp = Some(0);
+        let line = farm.status_line(T);
+
+        assert!(!line.contains('\n'));
+        assert!(line.contains("Farm: Bob"));
+        assert!(line.contains(&Money::dollars(1_000).to_string()));
+        assert!(line.contains("Fields 3 (1 ready)"));
+    }
+
+    #[test]
+    fn unknown_crops_load_as_a_placeholder_and_are_reported() {
+        let mut farm = farm_with_money(Money::dollars(1_000));
+        farm.record_actions = true;
+        farm.buy_field(Crop::Wheat, T).unwrap();
+        let mut json = serde_json::to_value(&farm).unwrap();
+        json["fields"][0]["crop"] = "Banana".into();
+        json["max_level_bonus"] = serde_json::json!({ "Banana": 3, "Wheat": 1 });
+        json["actions"][0]["action"]["Buy"]["crop"] = "Banana".into();
+
+        let loaded = Farm::from_json(&json.to_string()).unwrap();
+        assert_eq!(loaded.fields[0].crop, UNKNOWN_CROP_PLACEHOLDER);
+        assert_eq!(loaded.max_level(Crop::Wheat), loaded.config().max_level(Crop::Wheat) + 1);
+        assert_eq!(loaded.actions[0].action, Action::Buy { field: loaded.fields[0].id, crop: UNKNOWN_CROP_PLACEHOLDER, cost: Money::dollars(10) });
+        assert_eq!(loaded.validate(), Err(vec![
+            ValidationIssue::UnknownCrop { field: 0, crop: "Banana".to_string(), placeholder: UNKNOWN_CROP_PLACEHOLDER },
+            ValidationIssue::UnknownCropDropped { section: "max_level_bonus", crop: "Banana".to_string() },
+            ValidationIssue::UnknownCropInLog { action: 0, crop: "Banana".to_string(), placeholder: UNKNOWN_CROP_PLACEHOLDER },
+        ]));
+    }
+
+    #[test]
+    fn liquidating_sells_every_field() {
+        let mut farm = farm_with_fields(&[Crop::Wheat, Crop::Carrot, Crop::Wheat]);
+        farm.fields[1].level = 4;
+        farm.fields[2].plant_timestamp = Some(T);
+        let values = farm.fields.iter().map(|f| farm.sell_value(f)).sum::<Money>();
+
+        assert_eq!(farm.liquidate(), values);
+        assert!(farm.fields.is_empty());
+        assert_eq!(farm.money, Money::dollars(1_000) + values);
+    }
+
+    #[test]
+    fn fields_planted_together_get_different_ready_times() {
+        let mut farm = farm_with_fields(&[Crop::Wheat, Crop::Wheat]);
+        farm.seed = 42;
+        let base = farm.fields[0].base_grow_time(farm.config());
+        let band = base / 10;
+        for field in farm.fields.iter_mut() {
+            field.plant_timestamp = Some(T);
+            field.grow_jitter_ms = grow_jitter(&farm.config, farm.seed, field, T);
+        }
+
+        let ready = farm.fields.iter().map(|f| T + f.grow_time(farm.config())).collect::<Vec<u128>>();
+        assert_ne!(ready[0], ready[1]);
+        for ready in ready {
+            assert!((T + base - band..=T + base + band).contains(&ready));
+        }
+    }
+
+    #[test]
+    fn grow_jitter_can_be_turned_off() {
+        let config = GameConfig { grow_jitter: Some(0.), ..GameConfig::default() };
+        assert_eq!(grow_jitter(&config, 42, &Field::new(Crop::Wheat), T), 0);
+    }
+
+    #[test]
+    fn is_full_flips_at_the_field_limit() {
+        let mut farm = farm_with_money(Money::dollars(1_000));
+        farm.set_config(Arc::new(GameConfig { max_fields: Some(2), ..GameConfig::default() }));
+        farm.buy_field(Crop::Wheat, T).unwrap();
+        assert_eq!(farm.field_count(), 1);
+        assert!(!farm.is_full());
+        farm.buy_field(Crop::Wheat, T).unwrap();
+        assert!(farm.is_full());
+        assert_eq!(farm.buy_field(Crop::Wheat, T), Err(GameError::MaxFieldsReached));
+    }
+
+    #[test]
+    fn replaying_the_log_rebuilds_the_farm() {
+        let mut farm = farm_with_money(Money::dollars(1_000));
+        farm.record_actions = true;
+        let start = farm.clone();
+        farm.buy_fields(Crop::Wheat, 2, T).unwrap();
+        farm.buy_greenhouse(0).unwrap();
+        farm.buy_seeds(Crop::Wheat, 1).unwrap();
+        farm.plant_field(0).unwrap();
+        farm.level_up_field(0).unwrap();
+        farm.buy_insurance(0).unwrap();
+        farm.research_max_level(Crop::Wheat).unwrap();
+        farm.claim_daily_bonus(T).unwrap();
+        let ready_at = farm.fields[0].plant_timestamp.unwrap() + farm.fields[0].grow_time(farm.config());
+        farm.harvest(0, ready_at).unwrap();
+        farm.sell_field(1).unwrap();
+
+        let replayed = start.replay(&farm.actions).unwrap();
+        assert_eq!(serde_json::to_value(&replayed).unwrap(), serde_json::to_value(&farm).unwrap());
+    }
+
+    #[test]
+    fn replay_saves_load_as_the_replayed_farm() {
+        let mut farm = farm_with_money(Money::dollars(1_000));
+        farm.record_actions = true;
+        let start = farm.clone();
+        farm.buy_fields(Crop::Wheat, 2, T).unwrap();
+        farm.sell_field(0).unwrap();
+
+        let json = serde_json::to_string(&SaveData::Replay { start, actions: farm.actions.clone() }).unwrap();
+        let loaded = Farm::from_json(&json).unwrap();
+        assert_eq!(loaded.money, farm.money);
+        assert_eq!(loaded.fields.iter().map(|f| f.id).collect::<Vec<FieldId>>(), vec![farm.fields[0].id]);
+    }
+
+    #[test]
+    fn compact_save_keeps_only_the_latest_actions() {
+        let mut farm = farm_with_money(Money::dollars(1_000));
+        farm.record_actions = true;
+        for _ in 0..COMPACT_LOG_LENGTH / 2 + 10 {
+            farm.buy_field(Crop::Wheat, T).unwrap();
+            farm.sell_field(0).unwrap();
+        }
+
+        let loaded = Farm::from_json(&farm.compact_save()).unwrap();
+        assert_eq!(loaded.actions.len(), COMPACT_LOG_LENGTH);
+        assert_eq!(loaded.actions.last(), farm.actions.last());
+        assert_eq!(loaded.money, farm.money);
+    }
+
+    #[test]
+    fn plain_farm_saves_still_load() {
+        let farm = farm_with_fields(&[Crop::Wheat, Crop::Carrot]);
+        let loaded = Farm::from_json(&serde_json::to_string(&farm).unwrap()).unwrap();
+        assert_eq!(serde_json::to_value(&loaded).unwrap(), serde_json::to_value(&farm).unwrap());
+    }
+
+    #[test]
+    fn wheat_earns_more_in_its_season() {
+        let farm = farm_with_fields(&[Crop::Wheat]);
+        let week = util::SEASON_DAYS as u128 * util::DAY_MILLIS;
+        let (summer, winter) = (week, 3 * week);
+        assert_eq!(farm.current_season(summer), Season::Summer);
+        assert_eq!(farm.current_season(winter), Season::Winter);
+
+        let base = farm.field_earnings(&farm.fields[0], winter);
+        assert_eq!(base, Money::from_cents(150));
+        assert_eq!(farm.field_earnings(&farm.fields[0], summer), base * IN_SEASON_BONUS);
+    }
+
+    #[test]
+    fn planting_a_planted_field_costs_nothing() {
+        let mut farm = farm_with_fields(&[Crop::Wheat]);
+        farm.buy_seeds(Crop::Wheat, 2).unwrap();
+        farm.plant_field(0).unwrap();
+        let money = farm.money;
+        assert_eq!(farm.plant_field(0), Err(GameError::AlreadyPlanted));
+        assert_eq!(farm.money, money);
+        assert_eq!(farm.seed_count(Crop::Wheat), 1);
+    }
+
+    #[test]
+    fn clear_field_abandons_the_crop() {
+        let mut farm = farm_with_fields(&[Crop::Wheat]);
+        farm.buy_seeds(Crop::Wheat, 2).unwrap();
+        farm.plant_field(0).unwrap();
+        let money = farm.money;
+        farm.clear_field(0).unwrap();
+        assert!(!farm.fields[0].planted());
+        assert_eq!(farm.money, money);
+        assert_eq!(farm.clear_field(0), Err(GameError::NotPlanted));
+        farm.plant_field(0).unwrap();
+    }
+
+    #[test]
+    fn level_up_all_levels_the_cheapest_first() {
+        let mut farm = farm_with_fields(&[Crop::Potato, Crop::Wheat, Crop::Wheat]);
+        farm.fields[2].level = farm.max_level(Crop::Wheat);
+        farm.money = Money::dollars(10);
+
+        let summary = farm.level_up_all();
+        assert_eq!(summary.leveled, vec![1]);
+        assert_eq!(summary.too_expensive, vec![0]);
+        assert_eq!(summary.maxed, vec![2]);
+        assert_eq!(summary.cost, Money::from_cents(250));
+        assert_eq!(farm.fields.iter().map(|f| f.level).collect::<Vec<Level>>(), vec![1, 2, farm.max_level(Crop::Wheat)]);
+    }
+
+    #[test]
+    fn describe_field_reports_remaining_time_and_prices() {
+        let mut farm = farm_with_fields(&[Crop::Wheat]);
+        assert_eq!(farm.describe_field(&farm.fields[0], T).remaining_ms, None);
+        farm.fields[0].plant_timestamp = Some(T - 1_000);
+
+        let view = farm.describe_field(&farm.fields[0], T);
+        assert!(view.planted);
+        assert_eq!(view.remaining_ms, Some(farm.config().grow_time(Crop::Wheat) - 1_000));
+        assert_eq!(view.earnings, farm.field_earnings(&farm.fields[0], T));
+        assert_eq!(view.sell_value, farm.preview_sell(0).unwrap());
+        assert_eq!(view.level_up_price, farm.level_up_price(&farm.fields[0]).ok());
+
+        farm.fields[0].plant_timestamp = Some(0);
+        assert_eq!(farm.describe_field(&farm.fields[0], T).remaining_ms, Some(0));
+    }
+
+    #[test]
+    fn tax_is_charged_once_per_interval() {
+        let mut farm = farm_with_fields(&[Crop::Wheat]);
+        farm.set_tax_rate(0.01).unwrap();
+        farm.last_tax = T - TAX_INTERVAL_MS + 1;
+        assert_eq!(farm.apply_tax(T), Money::ZERO);
+
+        let net_worth = farm.net_worth();
+        assert_eq!(farm.apply_tax(T + 1), net_worth * 0.01);
+        assert_eq!(farm.money, Money::dollars(1_000) - net_worth * 0.01);
+        assert_eq!(farm.apply_tax(T + 2), Money::ZERO);
+    }
+
+    #[test]
+    fn tax_sells_fields_when_money_runs_short() {
+        let mut farm = farm_with_fields(&[Crop::Wheat]);
+        farm.money = Money::ZERO;
+        farm.set_tax_rate(0.5).unwrap();
+        farm.last_tax = T - TAX_INTERVAL_MS;
+
+        assert_eq!(farm.apply_tax(T), Money::from_cents(250));
+        assert_eq!(farm.field_count(), 0);
+        assert_eq!(farm.money, Money::from_cents(250));
+    }
+
+    #[test]
+    fn paused_growth_resumes_where_it_was_saved() {
+        let mut farm = farm_with_fields(&[Crop::Wheat]);
+        farm.pause_growth_offline = true;
+        farm.last_saved = T;
+        farm.fields[0].plant_timestamp = Some(T - 1_000);
+        farm.fields[0].update_elapsed_growth(T);
+
+        farm.resume_growth(T + util::DAY_MILLIS);
+        assert_eq!(farm.fields[0].age(T + util::DAY_MILLIS), Some(1_000));
+        assert!(!farm.fields[0].is_ready(T + util::DAY_MILLIS, farm.config()));
+
+        farm.pause_growth_offline = false;
+        farm.fields[0].plant_timestamp = Some(T - 1_000);
+        farm.resume_growth(T + util::DAY_MILLIS);
+        assert_eq!(farm.fields[0].age(T + util::DAY_MILLIS), Some(util::DAY_MILLIS + 1_000));
+    }
+
+    #[test]
+    fn from_index_follows_shop_order() {
+        assert_eq!(Crop::from_index(0), Some(Crop::Wheat));
+        assert_eq!(Crop::from_index(2), Some(Crop::Carrot));
+        assert_eq!(Crop::from_index(Crop::iter().count()), None);
+    }
+
+    #[test]
+    fn time_until_next_ready_is_the_smallest_wait() {
+        let mut farm = farm_with_fields(&[Crop::Wheat, Crop::Wheat, Crop::Carrot]);
+        assert_eq!(farm.time_until_next_ready(T), None);
+
+        farm.fields[0].plant_timestamp = Some(T - 10_000);
+        farm.fields[2].plant_timestamp = Some(T - 10_000);
+        assert_eq!(farm.time_until_next_ready(T), Some(farm.config().grow_time(Crop::Wheat) - 10_000));
+        farm.fields[1].plant_timestamp = Some(T - 95_000);
+        assert_eq!(farm.time_until_next_ready(T), Some(5_000));
+        farm.fields[1].plant_timestamp = Some(0);
+        assert_eq!(farm.time_until_next_ready(T), Some(0));
+        assert_eq!(farm_with_fields(&[]).time_until_next_ready(T), None);
+    }
+
+    #[test]
+    fn event_handler_sees_the_harvest_payout() {
+        let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut farm = farm_with_fields(&[Crop::Wheat]);
+        let seen = events.clone();
+        farm.set_event_handler(Box::new(move |event| seen.lock().unwrap().push(event.clone())));
+        farm.fields[0].plant_timestamp = Some(0);
+        let payout = farm.field_earnings(&farm.fields[0], T);
+        farm.harvest(0, T).unwrap();
+        farm.clone().buy_field(Crop::Wheat, T).unwrap();
+        farm.seeds.insert(Crop::Wheat, 1);
+
+        let snapshot = farm.snapshot();
+        farm.restore(snapshot);
+        farm.plant_field(0).unwrap();
+
+        let field = farm.fields[0].id;
+        let events = events.lock().unwrap();
+        assert_eq!(events[0], GameEvent::Action(Action::Harvest { field, payout }));
+        assert_eq!(events[1], GameEvent::Action(Action::Plant { field, cost: Money::ZERO }));
+        assert_eq!(events.len(), 2);
+    }
+
+    #[test]
+    fn inflation_raises_prices_but_not_earnings() {
+        let mut farm = farm_with_fields(&[Crop::Wheat]);
+        let (level_up, earnings) = (farm.level_up_price(&farm.fields[0]).unwrap(), farm.field_earnings(&farm.fields[0], T));
+        farm.set_inflation_rate(0.1).unwrap();
+        farm.last_inflation = T - 2 * INFLATION_INTERVAL_MS;
+        farm.apply_inflation(T);
+
+        assert_eq!(farm.field_price(Crop::Wheat), Money::from_cents(1_210));
+        assert_eq!(farm.preview_buy(Crop::Wheat, T), Ok(Money::from_cents(1_210)));
+        assert_eq!(farm.planting_price(Crop::Wheat), Money::from_cents(121));
+        assert_eq!(farm.level_up_price(&farm.fields[0]), Ok(level_up * 1.21));
+        assert_eq!(farm.field_earnings(&farm.fields[0], T), earnings);
+    }
+
+    #[test]
+    fn undo_reverses_a_buy() {
+        let mut farm = farm_with_money(Money::dollars(1_000));
+        farm.push_undo(farm.snapshot());
+        farm.buy_field(Crop::Wheat, T).unwrap();
+        farm.undo().unwrap();
+        assert_eq!(farm.field_count(), 0);
+        assert_eq!(farm.money, Money::dollars(1_000));
+        assert_eq!(farm.undo(), Err(GameError::NothingToUndo));
+    }
+
+    #[test]
+    fn undo_history_keeps_only_the_latest_actions() {
+        let mut farm = farm_with_money(Money::dollars(1_000));
+        farm.buy_seeds(Crop::Wheat, UNDO_HISTORY_LIMIT as u32).unwrap();
+        for _ in 0..(UNDO_HISTORY_LIMIT + 2) / 2 {
+            farm.push_undo(farm.snapshot());
+            farm.buy_field(Crop::Wheat, T).unwrap();
+            farm.push_undo(farm.snapshot());
+            farm.plant_field(farm.field_count() as u32 - 1).unwrap();
+        }
+        assert_eq!(farm.undo_count(), UNDO_HISTORY_LIMIT);
+        for _ in 0..UNDO_HISTORY_LIMIT { farm.undo().unwrap() }
+        assert_eq!(farm.field_count(), 1);
+        assert_eq!(farm.undo(), Err(GameError::NothingToUndo));
+        assert_eq!(farm.field_count(), 1);
+    }
+
+    #[test]
+    fn planting_uses_up_a_seed() {
+        let mut farm = farm_with_fields(&[Crop::Wheat, Crop::Wheat]);
+        assert_eq!(farm.plant_field(0), Err(GameError::NoSeeds));
+        assert!(!farm.fields[0].planted());
+
+        assert_eq!(farm.buy_seeds(Crop::Wheat, 2), Ok(Money::dollars(2)));
+        assert_eq!(farm.money, Money::dollars(998));
+        farm.plant_field(0).unwrap();
+        assert_eq!(farm.seed_count(Crop::Wheat), 1);
+        assert_eq!(farm.money, Money::dollars(998));
+        assert_eq!(farm.buy_seeds(Crop::Wheat, 1_000), Err(GameError::InsufficientFunds));
+    }
+
+    #[test]
+    fn seeds_are_saved_and_unknown_ones_dropped() {
+        let mut farm = farm_with_fields(&[Crop::Wheat]);
+        farm.buy_seeds(Crop::Wheat, 3).unwrap();
+        let mut json = serde_json::to_value(&farm).unwrap();
+        assert_eq!(Farm::from_json(&json.to_string()).unwrap().seed_count(Crop::Wheat), 3);
+
+        json["seeds"] = serde_json::json!({ "Banana": 3, "Wheat": 1 });
+        let loaded = Farm::from_json(&json.to_string()).unwrap();
+        assert_eq!(loaded.seed_count(Crop::Wheat), 1);
+        assert_eq!(loaded.validate(), Err(vec![ValidationIssue::UnknownCropDropped { section: "seeds", crop: "Banana".to_string() }]));
+    }
+
+    #[test]
+    fn projected_balance_matches_two_manual_cycles() {
+        let mut farm = farm_with_fields(&[Crop::Wheat]);
+        farm.set_config(Arc::new(GameConfig { grow_jitter: Some(0.), ..GameConfig::default() }));
+        farm.fields[0].plant_timestamp = Some(T);
+        let grow = farm.fields[0].grow_time(farm.config());
+        let rest = farm.config().rest_time(Crop::Wheat);
+        let (first, second) = (T + grow, T + grow + rest + grow);
+        let mut harvested = farm.fields[0].clone();
+        harvested.harvest_count = 1;
+        let (first_earnings, second_earnings) = (farm.field_earnings(&farm.fields[0], first), farm.field_earnings(&harvested, second));
+        let planting = farm.planting_price(Crop::Wheat);
+
+        let manual = farm.money + first_earnings - planting + second_earnings - planting;
+        assert_eq!(farm.projected_balance(T, second - T, true), manual);
+        assert_eq!(farm.projected_balance(T, second - T, false), farm.money + first_earnings);
+        assert_eq!(farm.projected_balance(T, grow - 1, true), farm.money);
+        assert_eq!(farm.fields[0].plant_timestamp, Some(T));
+    }
+
+    #[test]
+    fn attention_list_covers_every_reason() {
+        let mut farm = farm_with_fields(&[Crop::Wheat, Crop::Wheat, Crop::Wheat, Crop::Potato]);
+        farm.fields[0].plant_timestamp = Some(0);
+        farm.fields[1].plant_timestamp = Some(T);
+        farm.fields[2].rest_until = Some(T + 1);
+        // Ready to harvest, and the empty potato field can be planted with a seed bought now
+        assert!(farm.fields[0].needs_attention(T, farm.config()));
+        assert!(!farm.fields[3].needs_attention(T, farm.config()));
+        assert_eq!(farm.attention_list(T), vec![0, 3]);
+
+        // Empty fields need a seed or the money for one
+        farm.money = Money::ZERO;
+        assert_eq!(farm.attention_list(T), vec![0]);
+        farm.seeds.insert(Crop::Potato, 1);
+        assert_eq!(farm.attention_list(T), vec![0, 3]);
+
+        // Resting soil needs nothing until the rest is over
+        farm.seeds.insert(Crop::Wheat, 1);
+        assert_eq!(farm.attention_list(T), vec![0, 3]);
+        assert_eq!(farm.attention_list(T + 1), vec![0, 2, 3]);
+    }
+
+    #[test]
+    fn sandbox_changes_stay_in_the_sandbox_until_committed() {
+        let farm = farm_with_money(Money::dollars(1_000));
+        let mut sandbox = farm.sandbox();
+        sandbox.buy_field(Crop::Wheat, T).unwrap();
+        assert!(sandbox.is_sandbox);
+        assert_eq!(farm.field_count(), 0);
+        assert_eq!(farm.money, Money::dollars(1_000));
+
+        let path = testkit::temp_dir("sandbox").join("save.json").to_string_lossy().into_owned();
+        sandbox.save_to_path(path.clone());
+        assert!(!std::path::Path::new(&path).exists());
+        sandbox.commit_sandbox();
+        assert!(!sandbox.is_sandbox);
+        sandbox.save_to_path(path.clone());
+        assert_eq!(Farm::read_from_path(&path).unwrap().field_count(), 1);
+    }
+
+    #[test]
+    fn owning_every_crop_raises_earnings() {
+        let single = farm_with_fields(&[Crop::Wheat, Crop::Wheat]);
+        assert_eq!(single.diversity_bonus(), 1.);
+
+        let diverse = farm_with_fields(&[Crop::Wheat, Crop::Potato, Crop::Carrot, Crop::Tomato]);
+        assert!(diverse.diversity_bonus() > 1.);
+        let (wheat, diverse_wheat) = (single.field_earnings(&single.fields[0], T), diverse.field_earnings(&diverse.fields[0], T));
+        assert_eq!(diverse_wheat, wheat * (1. + DIVERSITY_BONUS));
+    }
+
+    #[test]
+    fn set_money_rejects_debt() {
+        let mut farm = farm_with_money(Money::dollars(5));
+        assert_eq!(farm.set_money(Money::dollars(-1)), Err(GameError::NegativeAmount));
+        assert_eq!(farm.set_money(Money::from_dollars(f64::NEG_INFINITY)), Err(GameError::NegativeAmount));
+        assert_eq!(farm.money, Money::dollars(5));
+
+        // Money can't hold NaN, converting it gives zero
+        assert_eq!(farm.set_money(Money::from_dollars(f64::NAN)), Ok(()));
+        assert_eq!(farm.money, Money::ZERO);
+        assert_eq!(farm.set_money(Money::dollars(250)), Ok(()));
+        assert_eq!(farm.money, Money::dollars(250));
+    }
+
+    #[test]
+    fn repeated_harvests_deplete_the_soil_until_it_rests() {
+        let mut farm = farm_with_fields(&[Crop::Wheat]);
+        farm.record_actions = true;
+        let start = farm.clone();
+        let mut payouts = Vec::new();
+        for _ in 0..10 {
+            farm.fields[0].plant_timestamp = Some(0);
+            payouts.push(farm.harvest(0, T).unwrap().payout);
+        }
+        assert_eq!(payouts[0], farm.field_earnings(&Field::new(Crop::Wheat), T));
+        assert!(payouts[9] < payouts[0]);
+        assert_eq!(farm.fields[0].soil_health(), (1. - SOIL_DEPLETION).powi(10));
+
+        farm.rest_field(0).unwrap();
+        assert_eq!(farm.fields[0].soil_health(), 1.);
+        assert!(farm.fields[0].resting(util::timestamp()));
+        assert_eq!(farm.actions.last().unwrap().action, Action::Rest { field: farm.fields[0].id });
+        assert_eq!(farm.field_earnings(&farm.fields[0], T), payouts[0]);
+
+        let mut depleted = start;
+        depleted.fields[0].harvest_count = 10;
+        let rested = depleted.replay(&farm.actions[farm.actions.len() - 1..]).unwrap();
+        assert_eq!(rested.fields[0].soil_health(), 1.);
+    }
+
+    #[test]
+    fn json_values_round_trip_the_whole_farm() {
+        let mut farm = farm_with_money(Money::dollars(1_000));
+        farm.record_actions = true;
+        farm.buy_fields(Crop::Wheat, 2, T).unwrap();
+        farm.buy_seeds(Crop::Wheat, 3).unwrap();
+        farm.plant_field(0).unwrap();
+        farm.level_up_field(1).unwrap();
+        farm.toggle_auto_replant(1).unwrap();
+        farm.research_max_level(Crop::Wheat).unwrap();
+        farm.interest_rate = 0.01;
+        farm.set_tax_rate(0.05).unwrap();
+        farm.fields[1].harvest_count = 4;
+        farm.xp = 7;
+
+        let value = farm.to_json_value();
+        let loaded = Farm::from_json_value(value.clone()).unwrap();
+        assert_eq!(loaded.to_json_value(), value);
+        assert_eq!(serde_json::to_value(&loaded).unwrap(), serde_json::to_value(&farm).unwrap());
+        assert!(matches!(Farm::from_json_value(serde_json::json!([])), Err(LoadError::Parse(_))));
+    }
+
+    struct DoublePayouts;
+
+    impl PricingStrategy for DoublePayouts {
+        fn id(&self) -> &'static str {
+            "double-payouts"
+        }
+
+        fn payout(&self, config: &GameConfig, crop: Crop) -> Money {
+            config.payout(crop) * 2.
+        }
+    }
+
+    #[test]
+    fn pricing_strategy_doubles_earnings_and_harvests() {
+        let mut standard = farm_with_fields(&[Crop::Wheat]);
+        standard.fields[0].plant_timestamp = Some(T);
+        let mut doubled = standard.clone();
+        doubled.set_pricing(DoublePayouts);
+        let now = T + standard.fields[0].grow_time(standard.config());
+
+        let earnings = standard.field_earnings(&standard.fields[0], now);
+        assert_eq!(doubled.field_earnings(&doubled.fields[0], now), earnings * 2.);
+        assert_eq!(doubled.field_price(Crop::Wheat), standard.field_price(Crop::Wheat));
+        assert_eq!(standard.harvest(0, now).unwrap().payout, earnings);
+        assert_eq!(doubled.harvest(0, now).unwrap().payout, earnings * 2.);
+    }
+
+    #[test]
+    fn pricing_strategy_id_is_saved_and_checked_on_load() {
+        let mut farm = farm_with_money(Money::dollars(1_000));
+        assert_eq!(farm.pricing_strategy, STANDARD_PRICING_ID);
+        farm.set_pricing(DoublePayouts);
+
+        let mut loaded = Farm::from_json_value(farm.to_json_value()).unwrap();
+        assert_eq!(loaded.pricing_strategy, "double-payouts");
+        assert_eq!(loaded.pricing().id(), STANDARD_PRICING_ID);
+        assert_eq!(loaded.validate(), Err(vec![ValidationIssue::PricingStrategyMissing { saved: "double-payouts".to_string(), active: STANDARD_PRICING_ID }]));
+        loaded.set_pricing(DoublePayouts);
+        assert_eq!(loaded.validate(), Ok(()));
+    }
+
+    #[test]
+    fn field_ready_fires_once_per_planting_across_ticks() {
+        fn ready_events(farm: &mut Farm, from: u128, to: u128) -> usize {
+            (from..to).step_by(1_000).flat_map(|now| farm.tick(now)).filter(|e| matches!(e, GameEvent::FieldReady { .. })).count()
+        }
+        let mut farm = farm_with_fields(&[Crop::Wheat]);
+        farm.seed = 42;
+        farm.toggle_notify_on_ready();
+        farm.fields[0].plant(T).unwrap();
+        let ready = T + farm.fields[0].grow_time(farm.config());
+        assert_eq!(ready_events(&mut farm, ready - 5_000, ready + 5_000), 1);
+
+        farm.harvest(0, ready + 5_000).unwrap();
+        farm.fields[0].plant_after_rest(ready + 5_000).unwrap();
+        let ready_again = farm.fields[0].plant_timestamp.unwrap() + farm.fields[0].grow_time(farm.config());
+        assert_eq!(ready_events(&mut farm, ready + 5_000, ready_again + 5_000), 1);
+    }
+
+    #[test]
+    fn fields_by_crop_groups_indices() {
+        let farm = farm_with_fields(&[Crop::Wheat, Crop::Potato, Crop::Wheat, Crop::Tomato, Crop::Wheat]);
+        let groups = farm.fields_by_crop();
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups[&Crop::Wheat], vec![0, 2, 4]);
+        assert_eq!(groups[&Crop::Potato], vec![1]);
+        assert_eq!(groups[&Crop::Tomato], vec![3]);
+        assert!(!groups.contains_key(&Crop::Carrot));
+    }
+
+    #[test]
+    fn level_up_cost_to_sums_each_level_up_price() {
+        let mut farm = farm_with_fields(&[Crop::Wheat]);
+        farm.inflation_factor = 1.21;
+        farm.xp = Skill::CheapUpgrades.xp_cost();
+        farm.unlock_skill(Skill::CheapUpgrades).unwrap();
+        let max = farm.max_level(Crop::Wheat);
+
+        let mut field = farm.fields[0].clone();
+        let mut sum = Money::ZERO;
+        while let Ok(price) = farm.level_up_price(&field) {
+            sum += price;
+            field.level += 1;
+        }
+        assert_eq!(field.level, max);
+        assert_eq!(farm.level_up_cost_to(&farm.fields[0], max), Ok(sum));
+        assert_eq!(farm.level_up_cost_to(&farm.fields[0], 2), farm.level_up_price(&farm.fields[0]));
+        assert_eq!(farm.level_up_cost_to(&farm.fields[0], 1), Err(GameError::InvalidLevel));
+        assert_eq!(farm.level_up_cost_to(&farm.fields[0], max + 1), Err(GameError::MaxLevelReached));
+    }
+
+    #[test]
+    fn spectator_view_reflects_money_and_readiness() {
+        let mut farm = farm_with_fields(&[Crop::Wheat, Crop::Potato, Crop::Wheat]);
+        farm.fields[0].plant_timestamp = Some(0);
+        farm.fields[1].plant_timestamp = Some(T - 1_000);
+
+        let view = farm.spectator_view(T);
+        assert_eq!((view.name.as_str(), view.money), ("Test", Money::dollars(1_000)));
+        assert_eq!((view.ready_count, view.next_ready_ms), (1, Some(0)));
+        assert_eq!(view.fields.len(), 3);
+        assert_eq!(view.fields[0].remaining_ms, Some(0));
+        assert_eq!(view.fields[1].remaining_ms, Some(farm.fields[1].time_to_farm(T, farm.config())));
+        assert_eq!(view.fields[2].remaining_ms, None);
+
+        farm.harvest(0, T).unwrap();
+        let view = farm.spectator_view(T);
+        assert_eq!(view.money, farm.money);
+        assert_eq!(view.ready_count, 0);
     }
 }
@@ -0,0 +1,133 @@
+use std::cell::RefCell;
+
+use crate::util::GameError;
+
+thread_local! {
+    static LANG: RefCell<Option<Box<dyn Lang>>> = const { RefCell::new(None) };
+}
+
+/// English labels of the main menu, a menu message id is an index into this
+pub const MENU: [&str; 30] = [
+    "Exit",
+    "View farm",
+    "Plant field",
+    "Harvest field",
+    "Buy new field",
+    "Level up field",
+    "Sell field",
+    "Save game",
+    "Load game",
+    "Stats",
+    "Rename farm",
+    "Claim daily bonus",
+    "Insure field",
+    "Undo last action",
+    "Export fields to CSV",
+    "Quick-sell cheapest empty field",
+    "Toggle auto-replant",
+    "Convert field to another crop",
+    "Build greenhouse",
+    "Skills",
+    "Skip tutorial",
+    "Research higher max level",
+    "Clear field",
+    "Level up all fields",
+    "Toggle offline growth",
+    "Buy seeds",
+    "Sandbox mode",
+    "Hotkey mode",
+    "Rest field",
+    "Toggle ready bell",
+];
+
+/// A user-facing piece of text that can be translated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Message {
+    Error(GameError),
+    Menu(usize),
+}
+
+/// A translation of the game's text
+pub trait Lang {
+    /// The translated text, `None` falls back to English
+    fn text(&self, message: Message) -> Option<String>;
+}
+
+pub struct English;
+
+impl Lang for English {
+    fn text(&self, message: Message) -> Option<String> {
+        Some(english(message).to_string())
+    }
+}
+
+fn english(message: Message) -> &'static str {
+    match message {
+        Message::Error(GameError::InsufficientFunds) => "Insufficient funds",
+        Message::Error(GameError::MaxLevelReached) => "Max level reached",
+        Message::Error(GameError::OutOfBounds) => "Out of bounds",
+        Message::Error(GameError::AlreadyPlanted) => "Already planted",
+        Message::Error(GameError::AlreadyFarmed) => "Already farmed",
+        Message::Error(GameError::NotYetReady) => "Not yet ready",
+        Message::Error(GameError::MaxFieldsReached) => "Max fields reached",
+        Message::Error(GameError::EmptyName) => "Name can't be empty",
+        Message::Error(GameError::NameTooLong) => "Name is too long",
+        Message::Error(GameError::BonusAlreadyClaimed) => "Bonus already claimed today",
+        Message::Error(GameError::AlreadyInsured) => "Already insured",
+        Message::Error(GameError::NotPlanted) => "Not planted",
+        Message::Error(GameError::InvalidRate) => "Rate must be between 0 and 1",
+        Message::Error(GameError::NoEmptyFields) => "No empty fields",
+        Message::Error(GameError::InvalidConfig) => "Invalid config file",
+        Message::Error(GameError::UnknownField) => "Unknown field",
+        Message::Error(GameError::SameCrop) => "Field already grows that crop",
+        Message::Error(GameError::SoilResting) => "The soil is still resting after the last harvest",
+        Message::Error(GameError::PrerequisiteNotMet) => "You need a max level field of the previous crop first",
+        Message::Error(GameError::NotInRotation) => "That crop isn't sold today",
+        Message::Error(GameError::AlreadyUpgraded) => "Already upgraded",
+        Message::Error(GameError::NotEnoughXp) => "Not enough XP",
+        Message::Error(GameError::SkillAlreadyUnlocked) => "Skill already unlocked",
+        Message::Error(GameError::NothingToUndo) => "Nothing to undo",
+        Message::Error(GameError::NoSeeds) => "No seeds for this crop, buy some first",
+        Message::Error(GameError::NegativeAmount) => "Amount can't be negative",
+        Message::Error(GameError::InvalidLevel) => "Target level must be above the current level",
+        Message::Menu(i) => MENU.get(i).copied().unwrap_or(""),
+    }
+}
+
+/// Sets the language of the current thread, replacing the one set before
+pub fn set_lang(lang: impl Lang + 'static) {
+    LANG.with(|current| *current.borrow_mut() = Some(Box::new(lang)));
+}
+
+/// The text for `message` in the current thread's language
+pub fn tr(message: Message) -> String {
+    LANG.with(|current| current.borrow().as_ref().and_then(|lang| lang.text(message)))
+        .unwrap_or_else(|| english(message).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Pirate;
+
+    impl Lang for Pirate {
+        fn text(&self, message: Message) -> Option<String> {
+            match message {
+                Message::Error(GameError::InsufficientFunds) => Some("Ye be short o' doubloons".to_string()),
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn set_lang_changes_the_rendered_text() {
+        assert_eq!(GameError::InsufficientFunds.to_string(), "Insufficient funds");
+        set_lang(Pirate);
+        assert_eq!(GameError::InsufficientFunds.to_string(), "Ye be short o' doubloons");
+        assert_eq!(GameError::NotYetReady.to_string(), "Not yet ready");
+
+        set_lang(English);
+        assert_eq!(GameError::InsufficientFunds.to_string(), "Insufficient funds");
+    }
+}
@@ -0,0 +1,62 @@
+use std::path::{Path, PathBuf};
+
+use crate::{farm::Farm, money::Money, util::{self, LoadError}};
+
+#[derive(Debug, Clone)]
+pub struct LeaderboardEntry {
+    pub name: String,
+    pub path: PathBuf,
+    pub net_worth: Money,
+}
+
+/// Saved farms ranked by net worth, richest first
+#[derive(Debug, Clone, Default)]
+pub struct Leaderboard {
+    pub entries: Vec<LeaderboardEntry>,
+}
+
+impl Leaderboard {
+    /// Ranks every `.json` save in `dir`, saves that can't be read are skipped
+    pub fn from_dir(dir: impl AsRef<Path>) -> Result<Leaderboard, LoadError> {
+        let mut entries = Vec::new();
+        for entry in std::fs::read_dir(dir)? {
+            let Ok(entry) = entry else { continue };
+            let path = entry.path();
+            if path.extension().is_none_or(|extension| extension != "json") { continue }
+            let Some(farm) = path.to_str().and_then(|path| Farm::read_from_path(path).ok()) else { continue };
+            entries.push(LeaderboardEntry { name: farm.name.clone(), path, net_worth: farm.net_worth() });
+        }
+        entries.sort_by(|a, b| b.net_worth.cmp(&a.net_worth).then_with(|| a.name.cmp(&b.name)));
+        Ok(Leaderboard { entries })
+    }
+
+    /// One line per farm with its rank, name and net worth
+    pub fn render(&self) -> String {
+        self.entries.iter().enumerate()
+            .map(|(i, entry)| format!("{}. {} - {}", i + 1, util::strip_control(&entry.name), entry.net_worth))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testkit::{self, farm_with_money};
+
+    #[test]
+    fn ranks_saves_by_net_worth() {
+        let dir = testkit::temp_dir("leaderboard");
+        for (name, dollars) in [("Middle", 50), ("Richest", 500), ("Poorest", 5)] {
+            let mut farm = farm_with_money(Money::dollars(dollars));
+            farm.name = name.to_string();
+            farm.save_to_path(dir.join(format!("{name}.json")).to_string_lossy().into_owned());
+        }
+        std::fs::write(dir.join("broken.json"), "{").unwrap();
+        std::fs::write(dir.join("notes.txt"), "not a save").unwrap();
+
+        let leaderboard = Leaderboard::from_dir(&dir).unwrap();
+        assert_eq!(leaderboard.entries.iter().map(|e| e.name.as_str()).collect::<Vec<&str>>(), vec!["Richest", "Middle", "Poorest"]);
+        assert_eq!(leaderboard.render().lines().next(), Some("1. Richest - $500.00"));
+    }
+}
@@ -1,3 +1,14 @@
 pub mod cli;
+pub mod config;
 pub mod farm;
-pub mod util;
\ No newline at end of file
+pub mod lang;
+pub mod leaderboard;
+pub mod logging;
+pub mod money;
+pub mod pricing;
+pub mod shared;
+pub mod slots;
+pub mod util;
+
+#[cfg(any(test, feature = "testkit"))]
+pub mod testkit;
\ No newline at end of file
@@ -0,0 +1,149 @@
+//! Diagnostic logging through the `log` crate, only active with the `logging` feature.
+//! Without it these functions do nothing
+
+use std::fmt;
+
+use crate::farm::Action;
+
+#[cfg(feature = "logging")]
+struct StderrLogger;
+
+#[cfg(feature = "logging")]
+impl log::Log for StderrLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            eprintln!("[{} {}] {}", record.level(), record.target(), record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Sends log records at `level` and above to stderr, fails if a logger is already installed
+#[cfg(feature = "logging")]
+pub fn init(level: log::LevelFilter) -> Result<(), log::SetLoggerError> {
+    static LOGGER: StderrLogger = StderrLogger;
+    log::set_logger(&LOGGER)?;
+    log::set_max_level(level);
+    Ok(())
+}
+
+/// Calls `init` with the level in the `CLI_FARM_LOG` env var (e.g. `info`), if it is set
+#[cfg(feature = "logging")]
+pub fn init_from_env() {
+    let Some(level) = std::env::var("CLI_FARM_LOG").ok().and_then(|level| level.parse().ok()) else { return };
+    let _ = init(level);
+}
+
+#[cfg_attr(not(feature = "logging"), allow(unused_variables))]
+pub fn action(action: &Action) {
+    #[cfg(feature = "logging")]
+    match action {
+        Action::Buy { field, crop, cost } => log::info!("buy field={field} crop={} cost={cost}", crop.name()),
+        Action::Plant { field, cost } => log::info!("plant field={field} cost={cost}"),
+        Action::Harvest { field, payout } => log::info!("harvest field={field} payout={payout}"),
+        Action::Sell { field, payout } => log::info!("sell field={field} payout={payout}"),
+        Action::LevelUp { field, level, cost } => log::info!("level_up field={field} level={level} cost={cost}"),
+        Action::BuySeeds { crop, count, cost } => log::info!("buy_seeds crop={} count={count} cost={cost}", crop.name()),
+        Action::Greenhouse { field, cost } => log::info!("greenhouse field={field} cost={cost}"),
+        Action::Clear { field } => log::info!("clear field={field}"),
+        Action::Rest { field } => log::info!("rest field={field}"),
+        Action::Convert { field, crop, cost } => log::info!("convert field={field} crop={} cost={cost}", crop.name()),
+        Action::Research { crop, cost } => log::info!("research crop={} cost={cost}", crop.name()),
+        Action::UnlockSkill { skill } => log::info!("unlock_skill skill={skill:?}"),
+        Action::Insure { field, cost } => log::info!("insure field={field} cost={cost}"),
+        Action::DailyBonus { amount } => log::info!("daily_bonus amount={amount}"),
+        Action::Interest { amount } => log::info!("interest amount={amount}"),
+        Action::Tax { amount } => log::info!("tax amount={amount}"),
+        Action::Inflation { factor } => log::info!("inflation factor={factor}"),
+        Action::CropLoss { field, payout } => log::info!("crop_loss field={field} payout={payout}"),
+    }
+}
+
+#[cfg_attr(not(feature = "logging"), allow(unused_variables))]
+pub fn saved(path: &str) {
+    #[cfg(feature = "logging")]
+    log::info!("save path={path}");
+}
+
+#[cfg_attr(not(feature = "logging"), allow(unused_variables))]
+pub fn error(context: &str, error: &dyn fmt::Display) {
+    #[cfg(feature = "logging")]
+    log::error!("{context} error={error}");
+}
+
+#[cfg(all(test, feature = "logging"))]
+mod tests {
+    use std::{cell::RefCell, sync::Once};
+
+    use crate::{farm::Crop, money::Money, testkit::farm_with_money, util};
+
+    const T: u128 = 1_000 * util::DAY_MILLIS;
+
+    thread_local! {
+        /// Records logged by the current test's thread, tests run in parallel
+        static RECORDS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    }
+
+    struct CapturingLogger;
+
+    impl log::Log for CapturingLogger {
+        fn enabled(&self, _: &log::Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &log::Record) {
+            RECORDS.with(|records| records.borrow_mut().push(record.args().to_string()));
+        }
+
+        fn flush(&self) {}
+    }
+
+    fn capture() {
+        static LOGGER: CapturingLogger = CapturingLogger;
+        static INIT: Once = Once::new();
+        INIT.call_once(|| {
+            log::set_logger(&LOGGER).unwrap();
+            log::set_max_level(log::LevelFilter::Info);
+        });
+        RECORDS.with(|records| records.borrow_mut().clear());
+    }
+
+    fn records() -> Vec<String> {
+        RECORDS.with(|records| records.borrow().clone())
+    }
+
+    #[test]
+    fn harvest_is_logged_with_its_payout() {
+        capture();
+        let mut farm = farm_with_money(Money::dollars(1_000));
+        farm.buy_field(Crop::Wheat, T).unwrap();
+        farm.fields[0].plant_timestamp = Some(0);
+        let result = farm.harvest(0, T).unwrap();
+
+        let expected = format!("harvest field={} payout={}", result.field, result.payout);
+        assert!(records().contains(&expected));
+    }
+
+    #[test]
+    fn replays_and_simulations_are_not_logged() {
+        let mut farm = farm_with_money(Money::dollars(1_000));
+        farm.record_actions = true;
+        let start = farm.clone();
+        farm.buy_field(Crop::Wheat, T).unwrap();
+        farm.buy_seeds(Crop::Wheat, 1).unwrap();
+        farm.plant_field(0).unwrap();
+        let ready_at = farm.fields[0].plant_timestamp.unwrap() + farm.fields[0].grow_time(farm.config());
+        farm.harvest(0, ready_at).unwrap();
+        farm.fields[0].plant_timestamp = Some(T);
+
+        capture();
+        start.replay(&farm.actions).unwrap();
+        farm.projected_balance(T, util::DAY_MILLIS, true);
+        assert_eq!(records(), Vec::<String>::new());
+    }
+}
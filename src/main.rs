@@ -1,3 +1,16 @@
+use cli_farm::cli::{self, Options};
+
 fn main() {
-    cli_farm::cli::run();
+    #[cfg(feature = "logging")]
+    cli_farm::logging::init_from_env();
+    let options = Options::from_env();
+    if options.version {
+        println!("{}", cli::version_info());
+        return
+    }
+    if options.help {
+        println!("{}", cli::HELP);
+        return
+    }
+    cli::run_with(options);
 }
@@ -0,0 +1,138 @@
+use std::{fmt, iter::Sum, ops::{Add, AddAssign, Mul, Sub, SubAssign}};
+
+use serde::{Serialize, Deserialize, Serializer, Deserializer};
+
+use crate::util;
+
+/// An amount of money in whole cents, so balances don't drift like floats do.
+/// Saved as a number of dollars to stay compatible with older saves.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Money(i64);
+
+impl Money {
+    pub const ZERO: Money = Money(0);
+    pub const MAX: Money = Money(i64::MAX);
+
+    pub const fn dollars(dollars: i64) -> Money {
+        Money(dollars * 100)
+    }
+
+    pub const fn from_cents(cents: i64) -> Money {
+        Money(cents)
+    }
+
+    /// Rounds to the nearest cent, out of range values saturate and NaN becomes zero
+    pub fn from_dollars(dollars: f64) -> Money {
+        Money((dollars * 100.).round() as i64)
+    }
+
+    pub fn cents(self) -> i64 {
+        self.0
+    }
+
+    pub fn as_dollars(self) -> f64 {
+        self.0 as f64 / 100.
+    }
+
+    pub fn is_negative(self) -> bool {
+        self.0 < 0
+    }
+
+    /// Subtracts `other`, `None` if the result would be negative
+    pub fn checked_sub(self, other: Money) -> Option<Money> {
+        self.0.checked_sub(other.0).filter(|cents| *cents >= 0).map(Money)
+    }
+}
+
+impl Add for Money {
+    type Output = Money;
+
+    fn add(self, other: Money) -> Money {
+        Money(self.0.saturating_add(other.0))
+    }
+}
+
+impl AddAssign for Money {
+    fn add_assign(&mut self, other: Money) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for Money {
+    type Output = Money;
+
+    fn sub(self, other: Money) -> Money {
+        Money(self.0.saturating_sub(other.0))
+    }
+}
+
+impl SubAssign for Money {
+    fn sub_assign(&mut self, other: Money) {
+        *self = *self - other;
+    }
+}
+
+/// Rounds to the nearest cent, saturating at `Money::MAX` on overflow
+impl Mul<f64> for Money {
+    type Output = Money;
+
+    fn mul(self, factor: f64) -> Money {
+        Money((self.0 as f64 * factor).round() as i64)
+    }
+}
+
+impl Sum for Money {
+    fn sum<I: Iterator<Item = Money>>(iter: I) -> Money {
+        iter.fold(Money::ZERO, |total, money| total + money)
+    }
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&util::format_money(*self))
+    }
+}
+
+impl Serialize for Money {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(self.as_dollars())
+    }
+}
+
+impl<'de> Deserialize<'de> for Money {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Money, D::Error> {
+        f64::deserialize(deserializer).map(Money::from_dollars)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn small_amounts_add_up_exactly() {
+        let total: Money = std::iter::repeat_n(Money::from_dollars(0.1), 1_000).sum();
+        assert_eq!(total, Money::dollars(100));
+    }
+
+    #[test]
+    fn checked_sub_refuses_to_go_negative() {
+        assert_eq!(Money::dollars(5).checked_sub(Money::dollars(3)), Some(Money::dollars(2)));
+        assert_eq!(Money::dollars(5).checked_sub(Money::dollars(5)), Some(Money::ZERO));
+        assert_eq!(Money::dollars(5).checked_sub(Money::from_cents(501)), None);
+    }
+
+    #[test]
+    fn saved_as_dollars() {
+        let json = serde_json::to_string(&Money::from_cents(1_250)).unwrap();
+        assert_eq!(json, "12.5");
+        assert_eq!(serde_json::from_str::<Money>(&json).unwrap(), Money::from_cents(1_250));
+    }
+
+    #[test]
+    fn arithmetic_saturates() {
+        assert_eq!(Money::MAX + Money::dollars(1), Money::MAX);
+        assert_eq!(Money::MAX * 2., Money::MAX);
+        assert_eq!(Money::from_dollars(f64::NAN), Money::ZERO);
+    }
+}
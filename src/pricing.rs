@@ -0,0 +1,91 @@
+use std::sync::Arc;
+
+use crate::{config::GameConfig, farm::{Crop, Level}, money::Money};
+
+/// Id of `StandardPricing`, what farms saved before pricing strategies existed load with
+pub const STANDARD_PRICING_ID: &str = "standard";
+
+/// The prices and payouts of an economy, swap it with `Farm::set_pricing`.
+/// Each method defaults to the standard table in `config`, overrides included
+pub trait PricingStrategy: Send + Sync {
+    /// Saved with the farm, so a loaded farm can tell which strategy it was played with
+    fn id(&self) -> &'static str;
+
+    fn new_field_price(&self, config: &GameConfig, crop: Crop) -> Money {
+        config.new_field_price(crop)
+    }
+
+    fn planting_price(&self, config: &GameConfig, crop: Crop) -> Money {
+        config.planting_price(crop)
+    }
+
+    /// The price of leveling a field of `crop` up from `level`
+    fn level_up_price(&self, config: &GameConfig, crop: Crop, level: Level) -> Money {
+        config.next_level_price(crop, level)
+    }
+
+    /// What a level 0 field of `crop` pays per harvest, before level and other multipliers
+    fn payout(&self, config: &GameConfig, crop: Crop) -> Money {
+        config.payout(crop)
+    }
+}
+
+/// The game's own prices
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StandardPricing;
+
+impl PricingStrategy for StandardPricing {
+    fn id(&self) -> &'static str {
+        STANDARD_PRICING_ID
+    }
+}
+
+/// A shared pricing strategy, `StandardPricing` by default
+#[derive(Clone)]
+pub struct Pricing(Arc<dyn PricingStrategy>);
+
+impl Pricing {
+    pub fn new(strategy: impl PricingStrategy + 'static) -> Pricing {
+        Pricing(Arc::new(strategy))
+    }
+
+    pub fn strategy(&self) -> &dyn PricingStrategy {
+        self.0.as_ref()
+    }
+}
+
+impl Default for Pricing {
+    fn default() -> Self {
+        Pricing::new(StandardPricing)
+    }
+}
+
+/// A strategy with the config it prices from, what `Field`'s prices and earnings take.
+/// See `Farm::prices`
+#[derive(Clone, Copy)]
+pub struct Prices<'a> {
+    pub strategy: &'a dyn PricingStrategy,
+    pub config: &'a GameConfig,
+}
+
+impl<'a> Prices<'a> {
+    pub fn new(strategy: &'a dyn PricingStrategy, config: &'a GameConfig) -> Prices<'a> {
+        Prices { strategy, config }
+    }
+
+    pub fn new_field_price(&self, crop: Crop) -> Money {
+        self.strategy.new_field_price(self.config, crop)
+    }
+
+    pub fn planting_price(&self, crop: Crop) -> Money {
+        self.strategy.planting_price(self.config, crop)
+    }
+
+    pub fn level_up_price(&self, crop: Crop, level: Level) -> Money {
+        self.strategy.level_up_price(self.config, crop, level)
+    }
+
+    pub fn payout(&self, crop: Crop) -> Money {
+        self.strategy.payout(self.config, crop)
+    }
+}
@@ -0,0 +1,74 @@
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::{farm::{Crop, Farm, FarmSnapshot, SpectatorView}, money::Money, util::{self, Result}};
+
+/// A farm that can be shared between threads, every method locks it for the duration of the call
+#[derive(Clone)]
+pub struct SharedFarm(Arc<Mutex<Farm>>);
+
+impl SharedFarm {
+    pub fn new(farm: Farm) -> SharedFarm {
+        SharedFarm(Arc::new(Mutex::new(farm)))
+    }
+
+    /// Locks the farm, a panic in another thread while it was locked doesn't make it unusable
+    pub fn lock(&self) -> MutexGuard<'_, Farm> {
+        self.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Runs `f` with the farm locked
+    pub fn with<T>(&self, f: impl FnOnce(&mut Farm) -> T) -> T {
+        f(&mut self.lock())
+    }
+
+    pub fn buy_field(&self, crop: Crop, now: u128) -> Result<()> {
+        self.lock().buy_field(crop, now)
+    }
+
+    pub fn plant(&self, id: u32) -> Result<()> {
+        self.lock().plant_field(id)
+    }
+
+    /// Returns the payout and whether the field was automatically replanted
+    pub fn harvest(&self, id: u32) -> Result<(Money, bool)> {
+        self.lock().farm_field(id)
+    }
+
+    pub fn snapshot(&self) -> FarmSnapshot {
+        self.lock().snapshot()
+    }
+
+    /// The farm as viewers see it right now, only holds the lock while copying it out
+    pub fn spectator_view(&self) -> SpectatorView {
+        self.lock().spectator_view(util::timestamp())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{testkit::farm_with_money, util};
+
+    #[test]
+    fn concurrent_buys_never_overspend() {
+        let farm = SharedFarm::new(farm_with_money(Money::dollars(55)));
+        let now = util::timestamp();
+        let threads = (0..8).map(|_| {
+            let farm = farm.clone();
+            std::thread::spawn(move || farm.buy_field(Crop::Wheat, now).is_ok())
+        }).collect::<Vec<_>>();
+        let bought = threads.into_iter().filter_map(|thread| thread.join().ok()).filter(|bought| *bought).count();
+
+        assert_eq!(bought, 5);
+        assert_eq!(farm.with(|farm| farm.field_count()), bought);
+        assert_eq!(farm.lock().money, Money::dollars(5));
+    }
+
+    #[test]
+    fn lock_survives_a_panicking_thread() {
+        let farm = SharedFarm::new(farm_with_money(Money::ZERO));
+        let panicking = farm.clone();
+        let _ = std::thread::spawn(move || panicking.with(|_| panic!("poison the lock"))).join();
+        assert_eq!(farm.lock().money, Money::ZERO);
+    }
+}
@@ -0,0 +1,56 @@
+use std::path::{Path, PathBuf};
+
+use crate::{farm::Farm, util::LoadError};
+
+/// The file name of saves from before save slots
+pub const LEGACY_SAVE_NAME: &str = "save.json";
+
+/// A numbered save file in a saves directory, `slot<number>.json`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SaveSlot {
+    pub number: u32,
+    pub path: PathBuf,
+}
+
+impl SaveSlot {
+    pub fn in_dir(dir: impl AsRef<Path>, number: u32) -> SaveSlot {
+        SaveSlot { number, path: dir.as_ref().join(format!("slot{number}.json")) }
+    }
+
+    pub fn exists(&self) -> bool {
+        self.path.exists()
+    }
+}
+
+/// Copies a legacy `save.json` in `dir` into slot 1, returning the slot if it was migrated.
+/// Nothing happens if there is no legacy save or slot 1 is already taken. The legacy save is
+/// kept so older versions can still load it, and it is only copied if it loads
+pub fn migrate_legacy_save(dir: impl AsRef<Path>) -> Result<Option<SaveSlot>, LoadError> {
+    let legacy = dir.as_ref().join(LEGACY_SAVE_NAME);
+    let slot = SaveSlot::in_dir(&dir, 1);
+    if !legacy.exists() || slot.exists() { return Ok(None) }
+    let Some(legacy_path) = legacy.to_str() else { return Ok(None) };
+    Farm::read_from_path(legacy_path)?;
+    std::fs::copy(&legacy, &slot.path)?;
+    Ok(Some(slot))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{money::Money, testkit::{self, farm_with_money}};
+
+    #[test]
+    fn legacy_save_is_copied_into_slot_one() {
+        let dir = testkit::temp_dir("slots");
+        assert_eq!(migrate_legacy_save(&dir).unwrap(), None);
+        let legacy = dir.join(LEGACY_SAVE_NAME);
+        farm_with_money(Money::dollars(42)).save_to_path(legacy.to_string_lossy().into_owned());
+
+        let slot = migrate_legacy_save(&dir).unwrap().unwrap();
+        assert_eq!(slot, SaveSlot::in_dir(&dir, 1));
+        assert!(legacy.exists());
+        assert_eq!(Farm::read_from_path(slot.path.to_str().unwrap()).unwrap().money, Money::dollars(42));
+        assert_eq!(migrate_legacy_save(&dir).unwrap(), None);
+    }
+}
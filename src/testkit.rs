@@ -0,0 +1,32 @@
+//! Helpers for setting up farms in tests, built for the crate's own tests and with the `testkit` feature
+
+use std::path::PathBuf;
+
+use crate::{farm::{Crop, Farm, Field}, money::Money};
+
+pub fn farm_with_money(money: Money) -> Farm {
+    let mut farm = Farm::new("Test".to_string());
+    farm.set_money(money).expect("test farms can't start in debt");
+    farm
+}
+
+pub fn planted_field(crop: Crop, planted_at: u128) -> Field {
+    let mut field = Field::new(crop);
+    field.plant_timestamp = Some(planted_at);
+    field
+}
+
+/// Moves the farm `ms` into the future by shifting its timestamps back: plantings, soil rest,
+/// market events and when interest, tax, inflation, saves, autosaves, the daily bonus, market and storm rolls last happened.
+/// The daily bonus and rolls move by whole days and intervals
+pub fn advance(farm: &mut Farm, ms: u128) {
+    farm.shift_timestamps(ms);
+}
+
+/// An empty directory of its own under the system temp dir, `name` must be unique per test
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("cli_farm-{}-{name}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("can't create a temp dir");
+    dir
+}
@@ -1,14 +1,15 @@
 use std::fmt;
 
+use crate::{lang::{self, Message}, money::Money};
+
 /// Milliseconds since the UNIX epoch
 pub fn timestamp() -> u128 {
     let now = std::time::SystemTime::now();
     let since_the_epoch = now.duration_since(std::time::UNIX_EPOCH).expect("Time went backwards");
-    let millis = since_the_epoch.as_millis();
-    millis
+    since_the_epoch.as_millis()
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum GameError {
     InsufficientFunds,
     MaxLevelReached,
@@ -16,26 +17,187 @@ pub enum GameError {
     AlreadyPlanted,
     AlreadyFarmed,
     NotYetReady,
-    MaxFieldsReached
+    MaxFieldsReached,
+    EmptyName,
+    NameTooLong,
+    BonusAlreadyClaimed,
+    AlreadyInsured,
+    NotPlanted,
+    InvalidRate,
+    NoEmptyFields,
+    InvalidConfig,
+    UnknownField,
+    SameCrop,
+    SoilResting,
+    PrerequisiteNotMet,
+    NotInRotation,
+    AlreadyUpgraded,
+    NotEnoughXp,
+    SkillAlreadyUnlocked,
+    NothingToUndo,
+    NoSeeds,
+    NegativeAmount,
+    InvalidLevel,
 }
 
 impl fmt::Display for GameError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let s = match self {
-            GameError::InsufficientFunds => "Insufficient funds",
-            GameError::MaxLevelReached => "Max level reached",
-            GameError::OutOfBounds => "Out of bounds",
-            GameError::AlreadyPlanted => "Already planted",
-            GameError::AlreadyFarmed => "Already farmed",
-            GameError::NotYetReady => "Not yet ready",
-            GameError::MaxFieldsReached => "Max fields reached",
-        };
-        write!(f, "{s}")
+        write!(f, "{}", lang::tr(Message::Error(*self)))
     }
 }
 
 pub type Result<T> = core::result::Result<T, GameError>;
 
+/// Why a save couldn't be loaded
+#[derive(Debug)]
+pub enum LoadError {
+    Io(std::io::Error),
+    Parse(serde_json::Error),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Io(e) => write!(f, "Unable to read save: {e}"),
+            LoadError::Parse(e) => write!(f, "Save is corrupted: {e}"),
+        }
+    }
+}
+
+impl From<std::io::Error> for LoadError {
+    fn from(e: std::io::Error) -> LoadError {
+        LoadError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for LoadError {
+    fn from(e: serde_json::Error) -> LoadError {
+        LoadError::Parse(e)
+    }
+}
+
 pub fn seconds_to_millis(seconds: u128) -> u128 {
     seconds * 1000
 }
+
+pub const DAY_MILLIS: u128 = 24 * 60 * 60 * 1000;
+
+/// Removes ANSI escape sequences and other control characters
+pub fn strip_control(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' && chars.peek() == Some(&'[') {
+            chars.next();
+            // Parameters run until the final byte of the sequence
+            for c in chars.by_ref() {
+                if ('@'..='~').contains(&c) { break }
+            }
+            continue
+        }
+        if !c.is_control() { stripped.push(c) }
+    }
+    stripped
+}
+
+/// Formats a duration like "1h 2m" or "16m 40s", leaving out zero units
+pub fn format_duration_ms(ms: u128) -> String {
+    if ms == 0 { return "0s".to_string() }
+    if ms < 1000 { return "<1s".to_string() }
+    let seconds = ms / 1000;
+    let units = [(seconds / 3600, "h"), (seconds / 60 % 60, "m"), (seconds % 60, "s")];
+    units.iter()
+        .filter(|(amount, _)| *amount > 0)
+        .map(|(amount, unit)| format!("{amount}{unit}"))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// Days since the UNIX epoch
+pub fn day(timestamp: u128) -> u64 {
+    (timestamp / DAY_MILLIS) as u64
+}
+
+/// Days each in-game season lasts
+pub const SEASON_DAYS: u64 = 7;
+
+/// Which of the four seasons the timestamp falls in, 0 is spring
+pub fn season_index(timestamp: u128) -> usize {
+    (day(timestamp) / SEASON_DAYS % 4) as usize
+}
+
+/// Deterministic pseudo-random number derived from `seed` (splitmix64)
+pub fn random(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9E3779B97F4A7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
+}
+
+/// The amount with thousands separators, like "$1,234,567.89" or "-$5.00"
+pub fn format_money(m: Money) -> String {
+    let sign = if m.is_negative() { "-" } else { "" };
+    let cents = m.cents().unsigned_abs();
+    let dollars = (cents / 100).to_string();
+    let mut grouped = String::with_capacity(dollars.len() + dollars.len() / 3);
+    for (i, digit) in dollars.chars().enumerate() {
+        if i > 0 && (dollars.len() - i).is_multiple_of(3) { grouped.push(',') }
+        grouped.push(digit);
+    }
+    format!("{sign}${grouped}.{:02}", cents % 100)
+}
+
+/// Quotes a CSV value if it contains a separator, quote or newline
+pub fn csv_escape(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_escape_quotes_separators() {
+        assert_eq!(csv_escape("Wheat"), "Wheat");
+        assert_eq!(csv_escape("a,b"), "\"a,b\"");
+        assert_eq!(csv_escape("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+
+    #[test]
+    fn durations_use_the_largest_units() {
+        assert_eq!(format_duration_ms(0), "0s");
+        assert_eq!(format_duration_ms(999), "<1s");
+        assert_eq!(format_duration_ms(100_000), "1m 40s");
+        assert_eq!(format_duration_ms(3_600_000), "1h");
+        assert_eq!(format_duration_ms(3_661_000), "1h 1m 1s");
+    }
+
+    #[test]
+    fn strip_control_removes_escape_sequences() {
+        assert_eq!(strip_control("\x1b[31mRed\x1b[0m Farm"), "Red Farm");
+        assert_eq!(strip_control("Tab\tbell\x07"), "Tabbell");
+        assert_eq!(strip_control("Gård 🌾"), "Gård 🌾");
+    }
+
+    #[test]
+    fn seasons_last_a_week_and_cycle() {
+        let week = SEASON_DAYS as u128 * DAY_MILLIS;
+        assert_eq!(season_index(week - 1), 0);
+        assert_eq!(season_index(week), 1);
+        assert_eq!(season_index(4 * week), 0);
+    }
+
+    #[test]
+    fn format_money_groups_thousands() {
+        assert_eq!(format_money(Money::ZERO), "$0.00");
+        assert_eq!(format_money(Money::from_cents(999)), "$9.99");
+        assert_eq!(format_money(Money::dollars(1_000)), "$1,000.00");
+        assert_eq!(format_money(Money::from_cents(123_456_789)), "$1,234,567.89");
+        assert_eq!(format_money(Money::dollars(-5)), "-$5.00");
+        assert_eq!(format_money(Money::from_cents(-100_000_000)), "-$1,000,000.00");
+    }
+}